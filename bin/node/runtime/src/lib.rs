@@ -113,10 +113,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 265,
+	spec_version: 266,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 2,
+	transaction_version: 3,
 };
 
 /// Native version.
@@ -512,12 +512,21 @@ parameter_types! {
 	// fallback: no need to do on-chain phragmen initially.
 	pub const Fallback: pallet_election_provider_multi_phase::FallbackStrategy =
 		pallet_election_provider_multi_phase::FallbackStrategy::Nothing;
+	pub const MultiPhaseUnsignedPhaseMode: pallet_election_provider_multi_phase::UnsignedPhaseMode =
+		pallet_election_provider_multi_phase::UnsignedPhaseMode::AlwaysActive;
 
 	pub SolutionImprovementThreshold: Perbill = Perbill::from_rational_approximation(1u32, 10_000);
 
 	// miner configs
 	pub const MultiPhaseUnsignedPriority: TransactionPriority = StakingUnsignedPriority::get() - 1u64;
 	pub const MinerMaxIterations: u32 = 10;
+
+	// snapshot configs
+	pub const MultiPhaseSnapshotPageSize: u32 = 1024;
+	pub const MultiPhaseSnapshotClearPagesPerBlock: u32 = 16;
+
+	// the compute of the last 100 elections is kept in state.
+	pub const MultiPhaseComputeHistoryDepth: u32 = 100;
 	pub MinerMaxWeight: Weight = RuntimeBlockWeights::get()
 		.get(DispatchClass::Normal)
 		.max_extrinsic.expect("Normal extrinsics have a weight limit configured; qed")
@@ -530,13 +539,31 @@ impl pallet_election_provider_multi_phase::Config for Runtime {
 	type SignedPhase = SignedPhase;
 	type UnsignedPhase = UnsignedPhase;
 	type SolutionImprovementThreshold = MinSolutionScoreBump;
+	type MinUnsignedInterval = ();
+	type ElectionGap = ();
 	type MinerMaxIterations = MinerMaxIterations;
 	type MinerMaxWeight = MinerMaxWeight;
 	type MinerTxPriority = MultiPhaseUnsignedPriority;
+	type MinerObjective = ();
+	type MinerTimeBudget = ();
+	type ScorePriority = ();
+	type MinSelfStake = ();
+	type MinBackersPerWinner = ();
+	type MinWinnerSupport = ();
+	type RejectUnchangedSolutions = ();
+	type SnapshotPageSize = MultiPhaseSnapshotPageSize;
+	type SnapshotDelay = ();
+	type SnapshotClearPagesPerBlock = MultiPhaseSnapshotClearPagesPerBlock;
+	type MaxSnapshotVoters = ();
+	type MaxSnapshotTargets = ();
+	type ComputeHistoryDepth = MultiPhaseComputeHistoryDepth;
 	type DataProvider = Staking;
 	type OnChainAccuracy = Perbill;
 	type CompactSolution = pallet_staking::CompactAssignments;
 	type Fallback = Fallback;
+	type UnsignedPhaseMode = MultiPhaseUnsignedPhaseMode;
+	type OnPhaseTransition = ();
+	type EmergencyOrigin = EnsureRootOrHalfCouncil;
 	type WeightInfo = pallet_election_provider_multi_phase::weights::SubstrateWeight<Runtime>;
 	type BenchmarkingConfig = ();
 }
@@ -1366,6 +1393,28 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_election_provider_multi_phase_rpc_runtime_api::TwoPhaseApi<
+		Block,
+		BlockNumber,
+		pallet_staking::CompactAssignments,
+	> for Runtime {
+		fn current_phase() -> pallet_election_provider_multi_phase::Phase<BlockNumber> {
+			ElectionProviderMultiPhase::current_phase()
+		}
+		fn desired_targets() -> u32 {
+			ElectionProviderMultiPhase::desired_targets().unwrap_or_default()
+		}
+		fn mine_solution(
+			iters: u32,
+		) -> Option<
+			pallet_election_provider_multi_phase::RawSolution<pallet_staking::CompactAssignments>
+		> {
+			ElectionProviderMultiPhase::mine_solution(iters as usize, true)
+				.ok()
+				.map(|(solution, _)| solution)
+		}
+	}
+
 	impl pallet_mmr::primitives::MmrApi<
 		Block,
 		mmr::Leaf,
@@ -0,0 +1,45 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the multi phase election provider module.
+//!
+//! This allows offchain miners to learn whether they should be computing a solution, without
+//! reading and decoding the raw storage of the pallet. Miners that do not run an offchain worker,
+//! e.g. on an archive node, can also use it to mine a solution against the current snapshot.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+
+pub use pallet_election_provider_multi_phase::{Phase, RawSolution};
+
+sp_api::decl_runtime_apis! {
+	pub trait TwoPhaseApi<BlockNumber, Solution> where
+		BlockNumber: Codec,
+		Solution: Codec,
+	{
+		/// The current phase of the election.
+		fn current_phase() -> Phase<BlockNumber>;
+		/// The number of targets to elect in the ongoing round. Zero if no snapshot exists.
+		fn desired_targets() -> u32;
+		/// Mine a solution against the current snapshot, with `iters` balancing iterations.
+		///
+		/// `None` if no snapshot exists or mining fails. The solution is not checked for
+		/// feasibility.
+		fn mine_solution(iters: u32) -> Option<RawSolution<Solution>>;
+	}
+}
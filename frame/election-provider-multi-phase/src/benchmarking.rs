@@ -26,7 +26,7 @@ use frame_system::RawOrigin;
 use rand::{prelude::SliceRandom, rngs::SmallRng, SeedableRng};
 use sp_election_providers::Assignment;
 use sp_arithmetic::traits::One;
-use sp_runtime::InnerOf;
+use sp_runtime::{InnerOf, Percent};
 use sp_std::convert::TryInto;
 
 const SEED: u32 = 0;
@@ -38,6 +38,22 @@ fn solution_with_size<T: Config>(
 	size: SolutionOrSnapshotSize,
 	active_voters_count: u32,
 	desired_targets: u32,
+) -> RawSolution<CompactOf<T>> {
+	solution_with_size_and_skew::<T>(size, active_voters_count, desired_targets, Percent::zero())
+}
+
+/// Same as [`solution_with_size`], but the votes of the active voters are concentrated on a subset
+/// of the winners, mimicking a real nominator set where a few validators attract most of the
+/// nominations.
+///
+/// `skew` is the portion of the winners that are not popular. With zero skew, the votes are
+/// uniformly distributed among all winners. Regardless of `skew`, each winner is voted for at least
+/// once.
+fn solution_with_size_and_skew<T: Config>(
+	size: SolutionOrSnapshotSize,
+	active_voters_count: u32,
+	desired_targets: u32,
+	skew: Percent,
 ) -> RawSolution<CompactOf<T>> {
 	assert!(size.targets >= desired_targets, "must have enough targets");
 	assert!(
@@ -66,15 +82,33 @@ fn solution_with_size<T: Config>(
 		.cloned()
 		.collect::<Vec<_>>();
 
+	// the winners that attract most of the votes. Must be at least `LIMIT`, for each voter to be
+	// able to vote for distinct winners, but no more than all of the winners.
+	let limit = <CompactOf<T>>::LIMIT;
+	let popular_count = (desired_targets.saturating_sub(skew * desired_targets) as usize)
+		.max(limit)
+		.min(winners.len());
+	let popular = &winners[..popular_count];
+
+	// when skewed, the first few voters are used to make sure that all winners have some votes.
+	let covering_voters = if skew.is_zero() {
+		0
+	} else {
+		(desired_targets as usize + limit - 1) / limit
+	};
+	assert!(active_voters_count as usize >= covering_voters, "must have enough active voters");
+
 	// first generate active voters who must vote for a subset of winners.
 	let active_voters = (0..active_voters_count)
 		.map(|i| {
-			// chose a random subset of winners.
-			let winner_votes = winners
-				.as_slice()
-				.choose_multiple(&mut rng, <CompactOf<T>>::LIMIT)
-				.cloned()
-				.collect::<Vec<_>>();
+			let winner_votes = if (i as usize) < covering_voters {
+				(0..limit)
+					.map(|j| winners[(i as usize * limit + j) % winners.len()].clone())
+					.collect::<Vec<_>>()
+			} else {
+				// chose a random subset of the popular winners.
+				popular.choose_multiple(&mut rng, limit).cloned().collect::<Vec<_>>()
+			};
 			let voter = account::<T::AccountId>("Voter", i, SEED);
 			(voter, stake, winner_votes)
 		})
@@ -107,17 +141,15 @@ fn solution_with_size<T: Config>(
 		targets: targets.len() as u32,
 	});
 	<DesiredTargets<T>>::put(desired_targets);
-	<Snapshot<T>>::put(RoundSnapshot { voters: all_voters.clone(), targets: targets.clone() });
+	<MultiPhase<T>>::store_snapshot(all_voters.clone(), targets.clone());
+	<SnapshotCreatedAt<T>>::put(<frame_system::Pallet<T>>::block_number());
 
 	// write the snapshot to staking or whoever is the data provider.
 	T::DataProvider::put_snapshot(all_voters.clone(), targets.clone());
 
 	let cache = helpers::generate_voter_cache::<T>(&all_voters);
-	let stake_of = helpers::stake_of_fn::<T>(&all_voters, &cache);
 	let voter_index = helpers::voter_index_fn::<T>(&cache);
 	let target_index = helpers::target_index_fn_linear::<T>(&targets);
-	let voter_at = helpers::voter_at_fn::<T>(&all_voters);
-	let target_at = helpers::target_at_fn::<T>(&targets);
 
 	let assignments = active_voters
 		.iter()
@@ -136,7 +168,7 @@ fn solution_with_size<T: Config>(
 
 	let compact =
 		<CompactOf<T>>::from_assignment(assignments, &voter_index, &target_index).unwrap();
-	let score = compact.clone().score(&winners, stake_of, voter_at, target_at).unwrap();
+	let score = <MultiPhase<T>>::score_compact(&compact, &all_voters, &targets).unwrap();
 	let round = <MultiPhase<T>>::round();
 	RawSolution { compact, score, round }
 }
@@ -185,6 +217,31 @@ benchmarks! {
 		assert!(<MultiPhase<T>>::current_phase().is_unsigned());
 	}
 
+	on_initialize_snapshot_page {
+		// size of the page.
+		let p in 1 .. T::BenchmarkingConfig::VOTERS[1];
+
+		// the first page is the most expensive, as it also reads the targets.
+		let targets: Vec<T::AccountId> = (0..T::BenchmarkingConfig::TARGETS[1])
+			.map(|i| account("Targets", i, SEED))
+			.collect();
+		let voters = (0..p)
+			.map(|i| {
+				let votes = targets.iter().take(<CompactOf<T>>::LIMIT).cloned().collect::<Vec<_>>();
+				(account::<T::AccountId>("Voter", i, SEED), One::one(), votes)
+			})
+			.collect::<Vec<_>>();
+		T::DataProvider::put_snapshot(voters, targets);
+
+		assert!(<MultiPhase<T>>::snapshot().is_none());
+		assert!(<MultiPhase<T>>::current_phase().is_off());
+	}: {
+		<MultiPhase<T>>::on_initialize_snapshot_page(1u32.into(), p);
+	} verify {
+		assert!(<SnapshotVoters<T>>::get(0).map_or(false, |voters| voters.len() as u32 <= p));
+		assert!(<MultiPhase<T>>::current_phase().is_off());
+	}
+
 	#[extra]
 	create_snapshot {
 		assert!(<MultiPhase<T>>::snapshot().is_none());
@@ -246,6 +303,32 @@ benchmarks! {
 		assert_ok!(<MultiPhase<T>>::feasibility_check(raw_solution, ElectionCompute::Unsigned));
 		let _decoded_snap = <RoundSnapshot<T::AccountId> as Decode>::decode(&mut &*encoded_snapshot).unwrap();
 	}
+
+	// Same as `feasibility_check`, but with most of the votes concentrated on a few winners.
+	#[extra]
+	feasibility_check_skewed {
+		// number of votes in snapshot.
+		let v in (T::BenchmarkingConfig::VOTERS[0]) .. T::BenchmarkingConfig::VOTERS[1];
+		// number of targets in snapshot.
+		let t in (T::BenchmarkingConfig::TARGETS[0]) .. T::BenchmarkingConfig::TARGETS[1];
+		// number of assignments, i.e. compact.len(). This means the active nominators, thus must be
+		// a subset of `v` component.
+		let a in (T::BenchmarkingConfig::ACTIVE_VOTERS[0]) .. T::BenchmarkingConfig::ACTIVE_VOTERS[1];
+		// number of desired targets. Must be a subset of `t` component.
+		let d in (T::BenchmarkingConfig::DESIRED_TARGETS[0]) .. T::BenchmarkingConfig::DESIRED_TARGETS[1];
+
+		let size = SolutionOrSnapshotSize { voters: v, targets: t };
+		let raw_solution = solution_with_size_and_skew::<T>(size, a, d, Percent::from_percent(80));
+
+		assert_eq!(raw_solution.compact.voter_count() as u32, a);
+		assert_eq!(raw_solution.compact.unique_targets().len() as u32, d);
+
+		// encode the most significant storage item that needs to be decoded in the dispatch.
+		let encoded_snapshot = <MultiPhase<T>>::snapshot().unwrap().encode();
+	}: {
+		assert_ok!(<MultiPhase<T>>::feasibility_check(raw_solution, ElectionCompute::Unsigned));
+		let _decoded_snap = <RoundSnapshot<T::AccountId> as Decode>::decode(&mut &*encoded_snapshot).unwrap();
+	}
 }
 
 #[cfg(test)]
@@ -259,6 +342,10 @@ mod test {
 			assert_ok!(test_benchmark_feasibility_check::<Runtime>());
 		});
 
+		ExtBuilder::default().build_and_execute(|| {
+			assert_ok!(test_benchmark_feasibility_check_skewed::<Runtime>());
+		});
+
 		ExtBuilder::default().build_and_execute(|| {
 			assert_ok!(test_benchmark_submit_unsigned::<Runtime>());
 		});
@@ -275,6 +362,10 @@ mod test {
 			assert_ok!(test_benchmark_on_initialize_nothing::<Runtime>());
 		});
 
+		ExtBuilder::default().build_and_execute(|| {
+			assert_ok!(test_benchmark_on_initialize_snapshot_page::<Runtime>());
+		});
+
 		ExtBuilder::default().build_and_execute(|| {
 			assert_ok!(test_benchmark_create_snapshot::<Runtime>());
 		});
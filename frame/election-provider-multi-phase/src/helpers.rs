@@ -17,8 +17,11 @@
 
 //! Some helper functions/macros for this crate.
 
-use super::{Config, VoteWeight, CompactVoterIndexOf, CompactTargetIndexOf};
-use sp_std::{collections::btree_map::BTreeMap, convert::TryInto, boxed::Box, prelude::*};
+use super::{Config, VoteWeight, CompactVoterIndexOf, CompactTargetIndexOf, SnapshotVoters};
+use frame_support::traits::Get;
+use sp_std::{
+	cell::RefCell, collections::btree_map::BTreeMap, convert::TryInto, boxed::Box, prelude::*,
+};
 
 #[macro_export]
 macro_rules! log {
@@ -157,3 +160,42 @@ pub fn stake_of_fn<'a, T: Config>(
 		}
 	})
 }
+
+/// Lazily read the voters of the snapshot by index.
+///
+/// Each page of [`SnapshotVoters`] is decoded at most once, and only if one of its voters is
+/// accessed.
+///
+/// ## Warning
+///
+/// All the pages but the last one must be full, as created by
+/// [`crate::Pallet::store_snapshot`].
+pub struct PagedVoters<T: Config> {
+	page_size: usize,
+	pages: RefCell<BTreeMap<u32, Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>>>,
+}
+
+impl<T: Config> PagedVoters<T> {
+	/// Read the voters of the current snapshot.
+	pub fn of_snapshot() -> Self {
+		Self { page_size: T::SnapshotPageSize::get().max(1) as usize, pages: Default::default() }
+	}
+
+	/// The voter at `index` of the snapshot, if any.
+	pub fn get(&self, index: usize) -> Option<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		let page: u32 = (index / self.page_size).try_into().ok()?;
+		let mut pages = self.pages.borrow_mut();
+		if !pages.contains_key(&page) {
+			pages.insert(page, <SnapshotVoters<T>>::get(page)?);
+		}
+		pages.get(&page).and_then(|voters| voters.get(index % self.page_size)).cloned()
+	}
+
+	/// Same as [`Self::get`], with an index of type [`CompactVoterIndexOf`].
+	pub fn get_at(
+		&self,
+		index: CompactVoterIndexOf<T>,
+	) -> Option<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		<CompactVoterIndexOf<T> as TryInto<usize>>::try_into(index).ok().and_then(|i| self.get(i))
+	}
+}
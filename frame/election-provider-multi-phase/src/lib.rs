@@ -43,7 +43,18 @@
 //!
 //! Each of the phases can be disabled by essentially setting their length to zero. If both phases
 //! have length zero, then the pallet essentially runs only the fallback strategy, denoted by
-//! [`Config::FallbackStrategy`].
+//! [`Config::FallbackStrategy`]. More specifically:
+//!
+//! - a zero-length signed phase means that the pallet goes from [`Phase::Off`] straight to
+//!   [`Phase::Unsigned`], creating the snapshot at that point.
+//! - a zero-length unsigned phase means that the signed phase lasts until `elect`, at which point
+//!   the fallback is used, since no solution could have been queued.
+//!
+//! The snapshot is normally taken in the block that opens the first phase. To spread its cost,
+//! [`Config::SnapshotDelay`] can be used to start taking it that many blocks earlier, one page of
+//! [`Config::SnapshotPageSize`] voters per block. If it is not complete by the time the first
+//! phase opens, the partial snapshot is discarded and the full one is taken in that block.
+//!
 //! ### Signed Phase
 //!
 //!	In the signed phase, solutions (of type [`RawSolution`]) are submitted and queued on chain. A
@@ -104,8 +115,10 @@
 //! the weigh of the solution to [`pallet::Config::MinerMaxWeight`].
 //!
 //! The unsigned phase can be made passive depending on how the previous signed phase went, by
-//! setting the first inner value of [`Phase`] to `false`. For now, the signed phase is always
-//! active.
+//! setting the first inner value of [`Phase`] to `false`. This is governed by
+//! [`pallet::Config::UnsignedPhaseMode`]: with [`UnsignedPhaseMode::AlwaysActive`], the unsigned
+//! phase is always active, whereas with [`UnsignedPhaseMode::ActiveOnlyAfterSignedFailure`] it is
+//! passive if a solution is already queued by the end of the signed phase.
 //!
 //! ### Fallback
 //!
@@ -125,6 +138,13 @@
 //! 1. present *exactly* correct number of winners.
 //! 2. any assignment is checked to match with [`RoundSnapshot::voters`].
 //! 3. the claimed score is valid, based on the fixed point arithmetic accuracy.
+//! 4. all winners are viable candidates, as reported by
+//!    [`ElectionDataProvider::is_viable_candidate`].
+//! 5. each winner is self-backed with at least [`pallet::Config::MinSelfStake`], if configured.
+//! 6. each winner is backed by at least [`pallet::Config::MinBackersPerWinner`] distinct voters.
+//! 7. each winner has a total backing of at least [`pallet::Config::MinWinnerSupport`].
+//! 8. the set of winners differs from [`LastElected`], if
+//!    [`pallet::Config::RejectUnchangedSolutions`] is set.
 //!
 //! ## Accuracy
 //!
@@ -210,11 +230,11 @@ use frame_support::{
 	traits::{Currency, Get, ReservableCurrency},
 	weights::Weight,
 };
-use frame_system::{ensure_none, offchain::SendTransactionTypes};
+use frame_system::{ensure_none, ensure_root, ensure_signed, offchain::SendTransactionTypes};
 use sp_election_providers::{ElectionDataProvider, ElectionProvider, onchain};
 use sp_npos_elections::{
 	assignment_ratio_to_staked_normalized, is_score_better, CompactSolution, ElectionScore,
-	EvaluateSupport, PerThing128, Supports, VoteWeight,
+	EvaluateSupport, ExtendedBalance, PerThing128, Supports, VoteWeight,
 };
 use sp_runtime::{
 	transaction_validity::{
@@ -223,10 +243,10 @@ use sp_runtime::{
 	},
 	DispatchError, PerThing, Perbill, RuntimeDebug, SaturatedConversion,
 };
-use sp_std::prelude::*;
+use sp_std::{cell::RefCell, collections::btree_map::BTreeMap, prelude::*};
 use sp_arithmetic::{
 	UpperOf,
-	traits::{Zero, CheckedAdd},
+	traits::{Zero, CheckedAdd, Saturating},
 };
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
@@ -329,6 +349,57 @@ impl<Bn: PartialEq + Eq> Phase<Bn> {
 	}
 }
 
+/// Something that is notified upon the phase transitions of the pallet.
+///
+/// The weight of these hooks is not accounted for by the pallet, thus they should be cheap.
+pub trait OnPhaseTransition {
+	/// The signed phase has been opened.
+	fn on_signed_open() {}
+	/// The unsigned phase has been opened.
+	fn on_unsigned_open() {}
+	/// The election has been concluded, successfully or not, and the phase is now off.
+	fn on_elect() {}
+}
+
+impl OnPhaseTransition for () {}
+
+/// Something that decides which of two election scores is better.
+///
+/// Used whenever a new solution is compared against the queued one.
+pub trait ScorePriority {
+	/// Returns true if `this` is better than `that` by at least `threshold`.
+	fn is_better(this: ElectionScore, that: ElectionScore, threshold: Perbill) -> bool;
+}
+
+/// The standard lexicographic comparison of `[minimal_stake, sum_stake, sum_stake_squared]`, as
+/// defined by [`sp_npos_elections::is_score_better`].
+impl ScorePriority for () {
+	fn is_better(this: ElectionScore, that: ElectionScore, threshold: Perbill) -> bool {
+		is_score_better::<Perbill>(this, that, threshold)
+	}
+}
+
+/// A comparison that prioritizes the balance of the solution.
+///
+/// The variance (`sum_stake_squared`, to be minimized) dominates, and only if the two are equal
+/// within `threshold` are the `minimal_stake` and the `sum_stake` compared, in that order.
+pub struct BalanceFirst;
+
+impl ScorePriority for BalanceFirst {
+	fn is_better(this: ElectionScore, that: ElectionScore, threshold: Perbill) -> bool {
+		use sp_arithmetic::ThresholdOrd;
+		use sp_std::cmp::Ordering;
+
+		match this[2].tcmp(&that[2], threshold.mul_ceil(that[2])) {
+			Ordering::Less => true,
+			Ordering::Greater => false,
+			Ordering::Equal => {
+				is_score_better::<Perbill>([this[0], this[1], 0], [that[0], that[1], 0], threshold)
+			}
+		}
+	}
+}
+
 /// A configuration for the pallet to indicate what should happen in the case of a fallback i.e.
 /// reaching a call to `elect` with no good solution.
 #[cfg_attr(test, derive(Clone))]
@@ -342,6 +413,18 @@ pub enum FallbackStrategy {
 	Nothing,
 }
 
+/// A configuration for the pallet to indicate whether the unsigned phase is active, i.e. accepting
+/// unsigned solutions.
+#[cfg_attr(test, derive(Clone))]
+pub enum UnsignedPhaseMode {
+	/// The unsigned phase is always active.
+	AlwaysActive,
+	/// The unsigned phase is only active if no solution is queued by the end of the signed phase.
+	///
+	/// If there is no signed phase, the unsigned phase is active.
+	ActiveOnlyAfterSignedFailure,
+}
+
 /// The type of `Computation` that provided this election data.
 #[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug)]
 pub enum ElectionCompute {
@@ -351,6 +434,8 @@ pub enum ElectionCompute {
 	Signed,
 	/// Election was computed with an unsigned submission.
 	Unsigned,
+	/// Election was provided by [`Config::EmergencyOrigin`], without any checks.
+	Emergency,
 }
 
 impl Default for ElectionCompute {
@@ -375,6 +460,18 @@ pub struct RawSolution<C> {
 	round: u32,
 }
 
+impl<C> RawSolution<C> {
+	/// Cheaply check that the claimed score is internally consistent.
+	///
+	/// The minimal support can not exceed the sum of supports, and the sum of supports squared can
+	/// not exceed the square of the sum of supports. This says nothing about the score being
+	/// correct, which is only known after [`Pallet::feasibility_check`].
+	pub fn validate_score_shape(&self) -> bool {
+		let [min_support, sum_support, sum_support_squared] = self.score;
+		min_support <= sum_support && sum_support_squared <= sum_support.saturating_mul(sum_support)
+	}
+}
+
 impl<C: Default> Default for RawSolution<C> {
 	fn default() -> Self {
 		// Round 0 is always invalid, only set this to 1.
@@ -396,12 +493,17 @@ pub struct ReadySolution<A> {
 	score: ElectionScore,
 	/// How this election was computed.
 	compute: ElectionCompute,
+	/// The round for which this solution was computed.
+	///
+	/// A solution is only ever enacted in this round, see [`Pallet::elect_with_compute`].
+	round: u32,
 }
 
 /// A snapshot of all the data that is needed for en entire round. They are provided by
 /// [`ElectionDataProvider`] and are kept around until the round is finished.
 ///
-/// These are stored together because they are often accessed together.
+/// The targets and voters are stored separately, the latter in pages of at most
+/// [`Config::SnapshotPageSize`] voters, and assembled back together by [`Pallet::snapshot`].
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
 pub struct RoundSnapshot<A> {
 	/// All of the voters.
@@ -463,6 +565,8 @@ impl From<unsigned::MinerError> for ElectionError {
 pub enum FeasibilityError {
 	/// Wrong number of winners presented.
 	WrongWinnerCount,
+	/// The desired number of targets of the snapshot is zero, thus no solution can be feasible.
+	ZeroDesiredTargets,
 	/// The snapshot is not available.
 	///
 	/// Kinda defensive: The pallet should technically never attempt to do a feasibility check when
@@ -476,10 +580,20 @@ pub enum FeasibilityError {
 	InvalidVoter,
 	/// A winner is invalid.
 	InvalidWinner,
+	/// A winner is not a viable candidate, as reported by the data provider.
+	NonViableWinner,
 	/// The given score was invalid.
 	InvalidScore,
 	/// The provided round is incorrect.
 	InvalidRound,
+	/// A winner is not self-backed with at least [`pallet::Config::MinSelfStake`].
+	InsufficientSelfStake,
+	/// A winner is backed by less than [`pallet::Config::MinBackersPerWinner`] voters.
+	InsufficientBackers,
+	/// A winner has a total backing of less than [`pallet::Config::MinWinnerSupport`].
+	InsufficientWinnerSupport,
+	/// The set of winners is identical to the one of the last election.
+	NoChange,
 }
 
 impl From<sp_npos_elections::Error> for FeasibilityError {
@@ -514,6 +628,17 @@ pub mod pallet {
 		#[pallet::constant]
 		type SolutionImprovementThreshold: Get<Perbill>;
 
+		/// The minimum number of blocks between two accepted unsigned solutions.
+		#[pallet::constant]
+		type MinUnsignedInterval: Get<Self::BlockNumber>;
+
+		/// The minimum number of blocks after a call to `elect` before any phase can open again.
+		///
+		/// This leaves room to inspect the outcome of an election before the next one starts,
+		/// regardless of [`ElectionDataProvider::next_election_prediction`].
+		#[pallet::constant]
+		type ElectionGap: Get<Self::BlockNumber>;
+
 		/// The priority of the unsigned transaction submitted in the unsigned-phase
 		type MinerTxPriority: Get<TransactionPriority>;
 		/// Maximum number of iteration of balancing that will be executed in the embedded miner of
@@ -524,6 +649,83 @@ pub mod pallet {
 		/// The miner will ensure that the total weight of the unsigned solution will not exceed
 		/// this values, based on [`WeightInfo::submit_unsigned`].
 		type MinerMaxWeight: Get<Weight>;
+		/// The objective that the miner optimizes for, when picking between candidate solutions.
+		type MinerObjective: unsigned::MinerObjective<Self::AccountId>;
+		/// Maximum wall-clock time, in milliseconds, that the offchain worker may spend mining.
+		///
+		/// Once exceeded, no more candidate solutions are computed and the best one so far is
+		/// used. Zero means no limit.
+		type MinerTimeBudget: Get<u64>;
+
+		/// How scores are compared when deciding if a solution improves the queued one.
+		type ScorePriority: ScorePriority;
+
+		/// The minimum amount of stake that each winner must back itself with.
+		///
+		/// This is checked against the self-vote of each winner in the final supports of a
+		/// solution. Zero disables the check.
+		#[pallet::constant]
+		type MinSelfStake: Get<VoteWeight>;
+
+		/// The minimum number of distinct voters that must back each winner.
+		///
+		/// Zero disables the check.
+		#[pallet::constant]
+		type MinBackersPerWinner: Get<u32>;
+
+		/// The minimum total backing of each winner.
+		///
+		/// Zero disables the check.
+		#[pallet::constant]
+		type MinWinnerSupport: Get<ExtendedBalance>;
+
+		/// If true, solutions electing exactly the same set of winners as the last election are
+		/// rejected.
+		///
+		/// Note that the fallback is not subject to this check.
+		#[pallet::constant]
+		type RejectUnchangedSolutions: Get<bool>;
+
+		/// The maximum number of voters stored in each page of [`SnapshotVoters`].
+		///
+		/// Zero is treated as one.
+		#[pallet::constant]
+		type SnapshotPageSize: Get<u32>;
+
+		/// The number of blocks before the first phase opens in which the snapshot starts being
+		/// taken, one page of voters per block.
+		///
+		/// Zero means that the whole snapshot is taken when the first phase opens.
+		#[pallet::constant]
+		type SnapshotDelay: Get<Self::BlockNumber>;
+
+		/// The maximum number of pages of [`SnapshotVoters`] removed per block once the snapshot
+		/// is killed, e.g. upon `elect`.
+		///
+		/// No phase opens until all the pages are removed. Zero means that all the pages are
+		/// removed at once, when the snapshot is killed.
+		#[pallet::constant]
+		type SnapshotClearPagesPerBlock: Get<u32>;
+
+		/// The maximum number of voters that the snapshot may contain.
+		///
+		/// If [`ElectionDataProvider::maybe_max_voters`] reports more, no phase is opened. Zero
+		/// means no limit.
+		#[pallet::constant]
+		type MaxSnapshotVoters: Get<u32>;
+
+		/// The maximum number of targets that the snapshot may contain.
+		///
+		/// If [`ElectionDataProvider::maybe_max_targets`] reports more, no phase is opened. Zero
+		/// means no limit.
+		#[pallet::constant]
+		type MaxSnapshotTargets: Get<u32>;
+
+		/// The number of most recent rounds for which [`ComputeHistory`] is kept.
+		///
+		/// Zero disables the history.
+		#[pallet::constant]
+		type ComputeHistoryDepth: Get<u32>;
 
 		/// Something that will provide the election data.
 		type DataProvider: ElectionDataProvider<Self::AccountId, Self::BlockNumber>;
@@ -543,6 +745,15 @@ pub mod pallet {
 		/// Configuration for the fallback
 		type Fallback: Get<FallbackStrategy>;
 
+		/// Configuration for whether the unsigned phase is active.
+		type UnsignedPhaseMode: Get<UnsignedPhaseMode>;
+
+		/// Something to notify upon phase transitions.
+		type OnPhaseTransition: OnPhaseTransition;
+
+		/// The origin that can queue a solution via [`Pallet::set_emergency_solution`].
+		type EmergencyOrigin: EnsureOrigin<Self::Origin>;
+
 		/// The configuration of benchmarking.
 		type BenchmarkingConfig: BenchmarkingConfig;
 
@@ -553,16 +764,73 @@ pub mod pallet {
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
 		fn on_initialize(now: T::BlockNumber) -> Weight {
-			let next_election = T::DataProvider::next_election_prediction(now).max(now);
+			// the snapshot of the last round is cleared before anything else happens.
+			if let Some(pages) = Self::clear_snapshot_pages() {
+				let pages = pages as Weight;
+				return T::DbWeight::get().reads_writes(pages + 1, pages + 1);
+			}
+
+			let current_phase = Self::current_phase();
 
-			let signed_deadline = T::SignedPhase::get() + T::UnsignedPhase::get();
+			// nothing opens within the gap that follows the last election.
+			let within_gap = Self::last_elect_block()
+				.map_or(false, |last| now < last.saturating_add(T::ElectionGap::get()));
+			if current_phase.is_off() && within_gap {
+				return T::WeightInfo::on_initialize_nothing();
+			}
+
+			// once a cycle has started, stick to the prediction made back then.
+			let prediction = Self::predicted_election_block()
+				.unwrap_or_else(|| T::DataProvider::next_election_prediction(now));
+
+			let signed_deadline = T::SignedPhase::get().saturating_add(T::UnsignedPhase::get());
 			let unsigned_deadline = T::UnsignedPhase::get();
 
-			let remaining = next_election - now;
-			let current_phase = Self::current_phase();
+			if prediction < now && current_phase.is_off() && !signed_deadline.is_zero() {
+				// the prediction is stale, we would otherwise never open any phase. Re-schedule the
+				// election as if the first non-empty phase opened now.
+				let rescheduled = now.saturating_add(signed_deadline);
+				log!(
+					warn,
+					"stale election prediction #{:?} at #{:?}, election re-scheduled at #{:?}.",
+					prediction,
+					now,
+					rescheduled,
+				);
+				Self::deposit_event(Event::PhaseTransitionFailed(prediction));
+				if !Self::snapshot_within_bounds() {
+					return T::WeightInfo::on_initialize_nothing();
+				}
+				<PredictedElectionBlock<T>>::put(rescheduled);
+				return if T::SignedPhase::get().is_zero() {
+					Self::on_initialize_open_unsigned(true, true, now);
+					T::WeightInfo::on_initialize_open_unsigned_with_snapshot()
+				} else {
+					Self::on_initialize_open_signed();
+					T::WeightInfo::on_initialize_open_signed()
+				};
+			}
+
+			let next_election = prediction.max(now);
+
+			// `next_election` is at least `now`, but be defensive.
+			let remaining = next_election.saturating_sub(now);
 
 			match current_phase {
+				Phase::Off
+					if remaining > signed_deadline
+						&& remaining <= signed_deadline.saturating_add(T::SnapshotDelay::get()) =>
+				{
+					// a page size of zero would never make progress.
+					let page_size = T::SnapshotPageSize::get().max(1);
+					Self::on_initialize_snapshot_page(now, page_size);
+					T::WeightInfo::on_initialize_snapshot_page(page_size)
+				}
 				Phase::Off if remaining <= signed_deadline && remaining > unsigned_deadline => {
+					if !Self::snapshot_within_bounds() {
+						return T::WeightInfo::on_initialize_nothing();
+					}
+					<PredictedElectionBlock<T>>::put(next_election);
 					Self::on_initialize_open_signed();
 					log!(info, "Starting signed phase at #{:?} , round {}.", now, Self::round());
 					T::WeightInfo::on_initialize_open_signed()
@@ -570,11 +838,24 @@ pub mod pallet {
 				Phase::Signed | Phase::Off
 					if remaining <= unsigned_deadline && remaining > 0u32.into() =>
 				{
+					if current_phase.is_off() {
+						if !Self::snapshot_within_bounds() {
+							return T::WeightInfo::on_initialize_nothing();
+						}
+						<PredictedElectionBlock<T>>::put(next_election);
+					}
+
 					let (need_snapshot, enabled, additional) = if current_phase == Phase::Signed {
 						// followed by a signed phase: close the signed phase, no need for snapshot.
 						// TWO_PHASE_NOTE: later on once we have signed phase, this should return
 						// something else.
-						(false, true, Weight::zero())
+						let enabled = match T::UnsignedPhaseMode::get() {
+							UnsignedPhaseMode::AlwaysActive => true,
+							UnsignedPhaseMode::ActiveOnlyAfterSignedFailure => {
+								Self::queued_solution_score().is_none()
+							}
+						};
+						(false, enabled, T::DbWeight::get().reads(1))
 					} else {
 						// no signed phase: create a new snapshot, definitely `enable` the unsigned
 						// phase.
@@ -600,8 +881,21 @@ pub mod pallet {
 			if Self::current_phase().is_unsigned_open_at(n) {
 				match Self::try_acquire_offchain_lock(n) {
 					Ok(_) => {
-						let outcome = Self::mine_check_and_submit().map_err(ElectionError::from);
-						log!(info, "miner exeuction done: {:?}", outcome);
+						let outcome = Self::mine_check_and_submit();
+						Self::record_miner_outcome(&outcome);
+						match outcome {
+							Err(unsigned::MinerError::NotWorthMining) => {
+								log!(debug, "queued solution is good enough, miner did not submit.")
+							}
+							Err(unsigned::MinerError::PendingSubmission) => log!(
+								debug,
+								"previous submission still pending, miner did not submit."
+							),
+							outcome => {
+								let outcome = outcome.map_err(ElectionError::from);
+								log!(info, "miner exeuction done: {:?}", outcome);
+							}
+						}
 					}
 					Err(why) => log!(warn, "denied offchain worker: {:?}", why),
 				}
@@ -692,15 +986,139 @@ pub mod pallet {
 
 			// store the newly received solution.
 			log!(info, "queued unsigned solution with score {:?}", ready.score);
+			<QueuedSolutionScore<T>>::put(ready.score);
 			<QueuedSolution<T>>::put(ready);
+			<LastUnsignedAt<T>>::put(<frame_system::Pallet<T>>::block_number());
 			Self::deposit_event(Event::SolutionStored(ElectionCompute::Unsigned));
 
 			Ok(None.into())
 		}
+
+		/// Force the next call to `elect` to ignore any queued solution and use the fallback.
+		///
+		/// The dispatch origin fo this call must be __root__.
+		///
+		/// The flag is reset once consumed by `elect`.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_force_fallback(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<ForceFallback<T>>::put(true);
+			Ok(None.into())
+		}
+
+		/// Abort the ongoing election.
+		///
+		/// The dispatch origin fo this call must be __root__.
+		///
+		/// The queued solution and the snapshot are discarded, the phase is set to `Off` and the
+		/// round is bumped. The snapshot is taken anew once the next phase opens. This is meant for
+		/// when the snapshot of the round is known to be bad, e.g. due to a faulty data provider.
+		///
+		/// The voter pages of the snapshot are removed in the following blocks, see
+		/// [`Config::SnapshotClearPagesPerBlock`], thus the weight of this call is constant.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 12))]
+		pub fn abort_election(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			let round = Self::round();
+			log!(warn, "aborting the election of round {}.", round);
+
+			<QueuedSolution<T>>::kill();
+			<QueuedSolutionScore<T>>::kill();
+			<Round<T>>::put(round + 1);
+			<CurrentPhase<T>>::put(Phase::Off);
+			Self::kill_snapshot_deferred();
+			<LastUnsignedAt<T>>::kill();
+			<PredictedElectionBlock<T>>::kill();
+
+			Self::deposit_event(Event::ElectionAborted(round));
+			Ok(None.into())
+		}
+
+		/// Queue a solution, bypassing all checks.
+		///
+		/// The dispatch origin fo this call must be [`Config::EmergencyOrigin`].
+		///
+		/// This is the last resort for a stuck election, e.g. when no solution is queued and the
+		/// fallback is too expensive to run on-chain. The solution is not checked for feasibility
+		/// whatsoever, it replaces any queued solution and is enacted upon the next `elect` of the
+		/// current round, with [`ElectionCompute::Emergency`].
+		///
+		/// The given score is ignored, the score is computed from the supports instead, such that
+		/// later solutions are compared against the real score.
+		#[pallet::weight(Pallet::<T>::emergency_solution_weight(&solution))]
+		pub fn set_emergency_solution(
+			origin: OriginFor<T>,
+			solution: ReadySolution<T::AccountId>,
+		) -> DispatchResultWithPostInfo {
+			T::EmergencyOrigin::ensure_origin(origin)?;
+			// the minimal support of no winners at all would be the maximum otherwise.
+			let score = if solution.supports.is_empty() {
+				Default::default()
+			} else {
+				(&solution.supports).evaluate()
+			};
+			let ready = ReadySolution {
+				score,
+				compute: ElectionCompute::Emergency,
+				round: Self::round(),
+				..solution
+			};
+
+			log!(warn, "queued emergency solution with score {:?}", ready.score);
+			<QueuedSolutionScore<T>>::put(ready.score);
+			<QueuedSolution<T>>::put(ready);
+			Self::deposit_event(Event::EmergencySolutionStored);
+
+			Ok(None.into())
+		}
+
+		/// Check the feasibility of a solution, without submitting it.
+		///
+		/// The dispatch origin fo this call must be __signed__.
+		///
+		/// This is meant for would-be signed submitters, to verify their solution before committing
+		/// to it. Nothing is stored; upon success, [`Event::FeasibilityDryRun`] is emitted with the
+		/// computed score. Only allowed during the signed phase.
+		///
+		/// Note that, although no deposit is taken, this is not free: the regular transaction fee
+		/// of the weight below is paid, whether or not the solution is feasible. A free call would
+		/// let anyone fill blocks with full feasibility checks at no cost.
+		///
+		/// The witness must match the snapshot, since it determines the weight of the call.
+		#[pallet::weight(T::WeightInfo::feasibility_check(
+			witness.voters,
+			witness.targets,
+			solution.compact.voter_count() as u32,
+			solution.compact.unique_targets().len() as u32
+		))]
+		pub fn feasibility_check_dry_run(
+			origin: OriginFor<T>,
+			solution: RawSolution<CompactOf<T>>,
+			witness: SolutionOrSnapshotSize,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::current_phase().is_signed(), Error::<T>::DryRunWrongPhase);
+			ensure!(
+				Self::snapshot_metadata().map_or(false, |size| size == witness),
+				Error::<T>::DryRunWrongWitness,
+			);
+
+			let ReadySolution { score, .. } =
+				Self::feasibility_check(solution, ElectionCompute::Signed).map_err(|e| {
+					log!(debug, "dry run feasibility check failed: {:?}", e);
+					Error::<T>::DryRunInfeasible
+				})?;
+
+			Self::deposit_event(Event::FeasibilityDryRun(who, score));
+			Ok(None.into())
+		}
 	}
 
 	#[pallet::event]
-	#[pallet::metadata(<T as frame_system::Config>::AccountId = "AccountId")]
+	#[pallet::metadata(
+		<T as frame_system::Config>::AccountId = "AccountId",
+		<T as frame_system::Config>::BlockNumber = "BlockNumber"
+	)]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// A solution was stored with the given compute.
@@ -709,8 +1127,8 @@ pub mod pallet {
 		/// solution is unsigned, this means that it has also been processed.
 		SolutionStored(ElectionCompute),
 		/// The election has been finalized, with `Some` of the given computation, or else if the
-		/// election failed, `None`.
-		ElectionFinalized(Option<ElectionCompute>),
+		/// election failed, `None`, electing the given number of winners.
+		ElectionFinalized(Option<ElectionCompute>, u32),
 		/// An account has been rewarded for their signed submission being finalized.
 		Rewarded(<T as frame_system::Config>::AccountId),
 		/// An account has been slashed for submitting an invalid signed submission.
@@ -719,6 +1137,22 @@ pub mod pallet {
 		SignedPhaseStarted(u32),
 		/// The unsigned phase of the given round has started.
 		UnsignedPhaseStarted(u32),
+		/// The data provider predicted the next election to be at the given block, which is in the
+		/// past. The first non-empty phase is opened immediately as a safety net, and the election
+		/// is re-scheduled as if it had been predicted from then on.
+		PhaseTransitionFailed(<T as frame_system::Config>::BlockNumber),
+		/// The desired number of targets reported by the data provider (first) was more than the
+		/// number of targets, thus it was clamped (second).
+		DesiredTargetsAdjusted(u32, u32),
+		/// The solution of a dry run by the given account is feasible, with the given score.
+		FeasibilityDryRun(<T as frame_system::Config>::AccountId, ElectionScore),
+		/// The election of the given round has been aborted by root.
+		ElectionAborted(u32),
+		/// No phase was opened, because the bounds on the number of voters (first) or targets
+		/// (second) reported by the data provider exceed the maximum size of the snapshot.
+		SnapshotTooLarge(Option<u32>, Option<u32>),
+		/// A solution was queued by [`Config::EmergencyOrigin`], without any checks.
+		EmergencySolutionStored,
 	}
 
 	/// Error of the pallet that can be returned in response to dispatches.
@@ -730,6 +1164,18 @@ pub mod pallet {
 		PreDispatchWrongWinnerCount,
 		/// Submission was too weak, score-wise.
 		PreDispatchWeakSubmission,
+		/// Submission was meant for a different round.
+		PreDispatchWrongRound,
+		/// Submission came too soon after the previous unsigned solution.
+		PreDispatchTooSoon,
+		/// A dry run was requested outside of the signed phase.
+		DryRunWrongPhase,
+		/// The solution of a dry run is not feasible.
+		DryRunInfeasible,
+		/// Submission has a score that is not internally consistent.
+		PreDispatchMalformedScore,
+		/// The witness of a dry run does not match the snapshot.
+		DryRunWrongWitness,
 	}
 
 	#[pallet::origin]
@@ -754,12 +1200,21 @@ pub mod pallet {
 					})
 					.map_err(dispatch_error_to_invalid)?;
 
+				// The later in the unsigned phase, the more urgent a solution is. This is in the order
+				// of blocks, thus the score still dominates.
+				let elapsed = match Self::current_phase() {
+					Phase::Unsigned((_, start)) => {
+						<frame_system::Pallet<T>>::block_number().saturating_sub(start)
+					}
+					_ => Zero::zero(),
+				};
+
 				ValidTransaction::with_tag_prefix("OffchainElection")
 					// The higher the score[0], the better a solution is.
 					.priority(
-						T::MinerTxPriority::get().saturating_add(
-							solution.score[0].saturated_into()
-						),
+						T::MinerTxPriority::get()
+							.saturating_add(solution.score[0].saturated_into())
+							.saturating_add(elapsed.saturated_into()),
 					)
 					// used to deduplicate unsigned solutions: each validator should produce one
 					// solution per round at most, and solutions are not propagate.
@@ -810,33 +1265,145 @@ pub mod pallet {
 	#[pallet::getter(fn queued_solution)]
 	pub type QueuedSolution<T: Config> = StorageValue<_, ReadySolution<T::AccountId>>;
 
-	/// Snapshot data of the round.
+	/// The score of [`QueuedSolution`].
+	///
+	/// Always kept in sync with [`QueuedSolution`], such that the score can be read without
+	/// decoding the entire solution.
+	#[pallet::storage]
+	#[pallet::getter(fn queued_solution_score)]
+	pub type QueuedSolutionScore<T: Config> = StorageValue<_, ElectionScore>;
+
+	/// The targets of the snapshot of the round.
+	///
+	/// This is created at the beginning of the signed phase, or ahead of it if
+	/// [`Config::SnapshotDelay`] is set, and cleared upon calling `elect`.
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_targets)]
+	pub type SnapshotTargets<T: Config> = StorageValue<_, Vec<T::AccountId>>;
+
+	/// The voters of the snapshot of the round, in pages of at most [`Config::SnapshotPageSize`].
 	///
-	/// This is created at the beginning of the signed phase and cleared upon calling `elect`.
+	/// Pages are contiguous and start at zero. Only exists when [`SnapshotTargets`] is present.
 	#[pallet::storage]
-	#[pallet::getter(fn snapshot)]
-	pub type Snapshot<T: Config> = StorageValue<_, RoundSnapshot<T::AccountId>>;
+	pub type SnapshotVoters<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u32,
+		Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>,
+	>;
 
 	/// Desired number of targets to elect for this round.
 	///
-	/// Only exists when [`Snapshot`] is present.
+	/// Only exists when [`SnapshotTargets`] is present.
 	#[pallet::storage]
 	#[pallet::getter(fn desired_targets)]
 	pub type DesiredTargets<T> = StorageValue<_, u32>;
 
 	/// The metadata of the [`RoundSnapshot`]
 	///
-	/// Only exists when [`Snapshot`] is present.
+	/// Only exists when [`SnapshotTargets`] is present.
 	#[pallet::storage]
 	#[pallet::getter(fn snapshot_metadata)]
 	pub type SnapshotMetadata<T: Config> = StorageValue<_, SolutionOrSnapshotSize>;
 
+	/// The block number at which the [`RoundSnapshot`] was created.
+	///
+	/// Only exists when [`SnapshotTargets`] is present.
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_created_at)]
+	pub type SnapshotCreatedAt<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The next page of [`SnapshotVoters`] to be taken, while the snapshot is being taken ahead of
+	/// the first phase. See [`Config::SnapshotDelay`].
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_in_progress)]
+	pub type SnapshotInProgress<T> = StorageValue<_, u32>;
+
+	/// The next page of [`SnapshotVoters`] to be removed, while the snapshot of the last round is
+	/// being cleared. See [`Config::SnapshotClearPagesPerBlock`].
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_clear_cursor)]
+	pub type SnapshotClearCursor<T> = StorageValue<_, u32>;
+
+	/// If set, the next `elect` ignores any queued solution and uses the fallback.
+	///
+	/// Set by [`Pallet::set_force_fallback`], and reset once consumed.
+	#[pallet::storage]
+	#[pallet::getter(fn force_fallback)]
+	pub type ForceFallback<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The block number at which [`ElectionProvider::elect`] was last called.
+	///
+	/// Useful for liveness monitoring: if this stops moving, the election is stuck.
+	#[pallet::storage]
+	#[pallet::getter(fn last_elect_block)]
+	pub type LastElectBlock<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The block number at which the last unsigned solution was accepted, in the current round.
+	#[pallet::storage]
+	#[pallet::getter(fn last_unsigned_at)]
+	pub type LastUnsignedAt<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The sorted set of winners of the last successful election.
+	///
+	/// Only kept if [`Config::RejectUnchangedSolutions`] is set.
+	#[pallet::storage]
+	#[pallet::getter(fn last_elected)]
+	pub type LastElected<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// The number of winners of the last election, zero if it failed.
+	///
+	/// This may be less than [`DesiredTargets`], e.g. if the fallback had too few targets.
+	#[pallet::storage]
+	#[pallet::getter(fn last_winner_count)]
+	pub type LastWinnerCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// The block at which the election of the current round is predicted to happen.
+	///
+	/// This is latched from [`ElectionDataProvider::next_election_prediction`] once the first phase
+	/// of the round opens, such that a jittery prediction can not alter the rest of the round, and
+	/// cleared upon calling `elect`.
+	#[pallet::storage]
+	#[pallet::getter(fn predicted_election_block)]
+	pub type PredictedElectionBlock<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The compute that provided the result of each of the last [`Config::ComputeHistoryDepth`]
+	/// successful elections, keyed by round.
+	#[pallet::storage]
+	#[pallet::getter(fn compute_history)]
+	pub type ComputeHistory<T: Config> = StorageMap<_, Twox64Concat, u32, ElectionCompute>;
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(PhantomData<T>);
 }
 
 impl<T: Config> Pallet<T> {
+	/// Whether the unsigned phase is active, i.e. accepting unsigned solutions.
+	///
+	/// Returns `None` if the current phase is not unsigned at all.
+	pub fn unsigned_phase_is_active() -> Option<bool> {
+		match Self::current_phase() {
+			Phase::Unsigned((active, _)) => Some(active),
+			_ => None,
+		}
+	}
+
+	/// The number of blocks left in the current phase, as of `now`.
+	///
+	/// The signed phase ends once the unsigned phase opens, and the unsigned phase ends at the
+	/// [`PredictedElectionBlock`]. Returns `None` if the phase is off, or if no prediction was
+	/// latched for the round.
+	pub fn phase_remaining(now: T::BlockNumber) -> Option<T::BlockNumber> {
+		let end = match Self::current_phase() {
+			Phase::Off => return None,
+			Phase::Signed =>
+				Self::predicted_election_block()?.saturating_sub(T::UnsignedPhase::get()),
+			Phase::Unsigned(_) => Self::predicted_election_block()?,
+		};
+		Some(end.saturating_sub(now))
+	}
+
 	/// Logic for `<Pallet as Hooks>::on_initialize` when signed phase is being opened.
 	///
 	/// This is decoupled for easy weight calculation.
@@ -844,6 +1411,7 @@ impl<T: Config> Pallet<T> {
 		<CurrentPhase<T>>::put(Phase::Signed);
 		Self::create_snapshot();
 		Self::deposit_event(Event::SignedPhaseStarted(Self::round()));
+		T::OnPhaseTransition::on_signed_open();
 	}
 
 	/// Logic for `<Pallet as Hooks<T>>::on_initialize` when unsigned phase is being opened.
@@ -856,39 +1424,268 @@ impl<T: Config> Pallet<T> {
 		now: T::BlockNumber,
 	) {
 		if need_snapshot {
-			// if not being followed by a signed phase, then create the snapshots.
-			debug_assert!(Self::snapshot().is_none());
+			// if not being followed by a signed phase, then create the snapshots, unless they were
+			// taken ahead of time.
 			Self::create_snapshot();
 		}
 
 		<CurrentPhase<T>>::put(Phase::Unsigned((enabled, now)));
 		Self::deposit_event(Event::UnsignedPhaseStarted(Self::round()));
+		T::OnPhaseTransition::on_unsigned_open();
+	}
+
+	/// Checks the bounds reported by the data provider against [`Config::MaxSnapshotVoters`] and
+	/// [`Config::MaxSnapshotTargets`], emitting [`Event::SnapshotTooLarge`] if they are exceeded.
+	///
+	/// Returns true if a snapshot can be taken.
+	pub(crate) fn snapshot_within_bounds() -> bool {
+		let exceeds = |bound: Option<u32>, max: u32| max > 0 && bound.map_or(false, |b| b > max);
+		let max_voters = T::DataProvider::maybe_max_voters();
+		let max_targets = T::DataProvider::maybe_max_targets();
+
+		if exceeds(max_voters, T::MaxSnapshotVoters::get())
+			|| exceeds(max_targets, T::MaxSnapshotTargets::get())
+		{
+			log!(
+				warn,
+				"snapshot of up to {:?} voters and {:?} targets is too large, not opening a phase.",
+				max_voters,
+				max_targets,
+			);
+			Self::deposit_event(Event::SnapshotTooLarge(max_voters, max_targets));
+			false
+		} else {
+			true
+		}
 	}
 
 	/// Creates the snapshot. Writes new data to:
 	///
 	/// 1. [`SnapshotMetadata`]
-	/// 2. [`RoundSnapshot`]
+	/// 2. [`SnapshotTargets`] and [`SnapshotVoters`]
 	/// 3. [`DesiredTargets`]
+	/// 4. [`SnapshotCreatedAt`]
+	///
+	/// The stake of each voter is adjusted via [`ElectionDataProvider::voter_weight_adjustment`]
+	/// before being stored. If there are less targets than the desired number of targets, the
+	/// latter is clamped and [`Event::DesiredTargetsAdjusted`] is emitted.
 	pub(crate) fn create_snapshot() {
+		if <SnapshotInProgress<T>>::take().is_some() {
+			log!(warn, "snapshot could not be taken ahead of time, taking it at once.");
+		} else if Self::snapshot_metadata().is_some() {
+			// already taken ahead of time.
+			return;
+		}
+
 		// if any of them don't exist, create all of them. This is a bit conservative.
 		let targets = T::DataProvider::targets();
-		let voters = T::DataProvider::voters();
-		let desired_targets = T::DataProvider::desired_targets();
+		let mut voters =
+			Vec::with_capacity(T::DataProvider::maybe_max_voters().unwrap_or_default() as usize);
+		voters.extend(T::DataProvider::voters().into_iter().map(|(who, stake, votes)| {
+			let stake = T::DataProvider::voter_weight_adjustment(&who, stake);
+			(who, stake, votes)
+		}));
+		let desired_targets = Self::clamp_desired_targets(targets.len() as u32);
 
 		<SnapshotMetadata<T>>::put(SolutionOrSnapshotSize {
 			voters: voters.len() as u32,
 			targets: targets.len() as u32,
 		});
 		<DesiredTargets<T>>::put(desired_targets);
-		<Snapshot<T>>::put(RoundSnapshot { voters, targets });
+		Self::store_snapshot(voters, targets);
+		<SnapshotCreatedAt<T>>::put(<frame_system::Pallet<T>>::block_number());
+	}
+
+	/// The weight of [`Pallet::set_emergency_solution`] with `solution`.
+	///
+	/// Evaluating and storing the supports is bounded by checking the feasibility of a solution
+	/// with as many voters and edges as there are backers, and as many targets as there are
+	/// winners.
+	fn emergency_solution_weight(solution: &ReadySolution<T::AccountId>) -> Weight {
+		let winners = solution.supports.len() as u32;
+		let backers = solution
+			.supports
+			.iter()
+			.fold(0u32, |acc, (_, support)| acc.saturating_add(support.voters.len() as u32));
+		T::WeightInfo::feasibility_check(backers, winners, backers, winners)
+			.saturating_add(T::DbWeight::get().reads_writes(1, 2))
+	}
+
+	/// Take the next page of at most `page_size` voters of the snapshot ahead of the first phase,
+	/// see [`Config::SnapshotDelay`]. `page_size` must be non-zero.
+	///
+	/// The first page also stores the targets and the desired targets. The snapshot is complete,
+	/// and [`SnapshotMetadata`] is stored, once the data provider returns less than a full page.
+	pub(crate) fn on_initialize_snapshot_page(now: T::BlockNumber, page_size: u32) {
+		let page = match Self::snapshot_in_progress() {
+			Some(page) => page,
+			// already complete.
+			None if Self::snapshot_metadata().is_some() => return,
+			None => {
+				if !Self::snapshot_within_bounds() {
+					return;
+				}
+				let targets = T::DataProvider::targets();
+				<DesiredTargets<T>>::put(Self::clamp_desired_targets(targets.len() as u32));
+				<SnapshotTargets<T>>::put(targets);
+				<SnapshotVoters<T>>::remove_all();
+				<SnapshotClearCursor<T>>::kill();
+				0
+			}
+		};
+
+		// resume right after the last voter of the previous page.
+		let last = page
+			.checked_sub(1)
+			.and_then(|previous| <SnapshotVoters<T>>::get(previous))
+			.and_then(|voters| voters.last().map(|(who, _, _)| who.clone()));
+		let voters = T::DataProvider::voters_after(last.as_ref(), page_size)
+			.into_iter()
+			.map(|(who, stake, votes)| {
+				let stake = T::DataProvider::voter_weight_adjustment(&who, stake);
+				(who, stake, votes)
+			})
+			.collect::<Vec<_>>();
+		let taken = voters.len() as u32;
+		if !voters.is_empty() {
+			<SnapshotVoters<T>>::insert(page, voters);
+		}
+
+		if taken < page_size {
+			let targets = <SnapshotTargets<T>>::decode_len().unwrap_or_default() as u32;
+			<SnapshotMetadata<T>>::put(SolutionOrSnapshotSize {
+				voters: page.saturating_mul(page_size).saturating_add(taken),
+				targets,
+			});
+			<SnapshotCreatedAt<T>>::put(now);
+			<SnapshotInProgress<T>>::kill();
+			log!(info, "snapshot taken ahead of time in {} pages at #{:?}.", page + 1, now);
+		} else {
+			<SnapshotInProgress<T>>::put(page + 1);
+		}
+	}
+
+	/// The desired targets of the data provider, clamped to the given number of targets.
+	fn clamp_desired_targets(max_desired_targets: u32) -> u32 {
+		let desired_targets = T::DataProvider::desired_targets();
+
+		// we can't elect more winners than there are targets.
+		if desired_targets > max_desired_targets {
+			log!(
+				warn,
+				"desired targets {} is more than the number of targets {}, clamping.",
+				desired_targets,
+				max_desired_targets,
+			);
+			Self::deposit_event(Event::DesiredTargetsAdjusted(
+				desired_targets,
+				max_desired_targets,
+			));
+			max_desired_targets
+		} else {
+			desired_targets
+		}
+	}
+
+	/// Store the given voters and targets as the snapshot, paging the voters into chunks of
+	/// [`Config::SnapshotPageSize`].
+	pub(crate) fn store_snapshot(
+		voters: Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>,
+		targets: Vec<T::AccountId>,
+	) {
+		<SnapshotVoters<T>>::remove_all();
+		<SnapshotClearCursor<T>>::kill();
+		let page_size = T::SnapshotPageSize::get().max(1) as usize;
+		voters
+			.chunks(page_size)
+			.enumerate()
+			.for_each(|(page, chunk)| <SnapshotVoters<T>>::insert(page as u32, chunk.to_vec()));
+		<SnapshotTargets<T>>::put(targets);
+	}
+
+	/// Lazily iterate over the pages of [`SnapshotVoters`], in order.
+	pub fn snapshot_voter_pages(
+	) -> impl Iterator<Item = Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>> {
+		(0u32..).scan((), |_, page| <SnapshotVoters<T>>::get(page))
+	}
+
+	/// The snapshot of the round, assembled from [`SnapshotTargets`] and all the pages of
+	/// [`SnapshotVoters`].
+	pub fn snapshot() -> Option<RoundSnapshot<T::AccountId>> {
+		let targets = Self::snapshot_targets()?;
+		let voters = Self::snapshot_voter_pages().flatten().collect::<Vec<_>>();
+		Some(RoundSnapshot { voters, targets })
 	}
 
 	/// Kill everything created by [`Pallet::create_snapshot`].
+	///
+	/// The pages of [`SnapshotVoters`] are only removed in the following blocks, if
+	/// [`Config::SnapshotClearPagesPerBlock`] is set.
 	pub(crate) fn kill_snapshot() {
-		<Snapshot<T>>::kill();
+		Self::kill_snapshot_deferred();
+		if T::SnapshotClearPagesPerBlock::get().is_zero() {
+			<SnapshotVoters<T>>::remove_all();
+			<SnapshotClearCursor<T>>::kill();
+		}
+	}
+
+	/// Same as [`Pallet::kill_snapshot`], but the pages of [`SnapshotVoters`] are always left to
+	/// [`Pallet::clear_snapshot_pages`], regardless of [`Config::SnapshotClearPagesPerBlock`].
+	///
+	/// This has a constant weight.
+	pub(crate) fn kill_snapshot_deferred() {
+		<SnapshotTargets<T>>::kill();
 		<SnapshotMetadata<T>>::kill();
 		<DesiredTargets<T>>::kill();
+		<SnapshotCreatedAt<T>>::kill();
+		<SnapshotInProgress<T>>::kill();
+
+		if !<SnapshotClearCursor<T>>::exists() {
+			<SnapshotClearCursor<T>>::put(0);
+		}
+	}
+
+	/// Remove up to [`Config::SnapshotClearPagesPerBlock`] pages of [`SnapshotVoters`] left over
+	/// by [`Pallet::kill_snapshot`], starting at [`SnapshotClearCursor`].
+	///
+	/// Returns the number of pages that were visited, or `None` if there is nothing to clear.
+	pub(crate) fn clear_snapshot_pages() -> Option<u32> {
+		let start = Self::snapshot_clear_cursor()?;
+		let end = start.saturating_add(T::SnapshotClearPagesPerBlock::get().max(1));
+
+		for page in start..end {
+			if !<SnapshotVoters<T>>::contains_key(page) {
+				log!(debug, "snapshot cleared, {} pages removed.", page);
+				<SnapshotClearCursor<T>>::kill();
+				return Some(page - start + 1);
+			}
+			<SnapshotVoters<T>>::remove(page);
+		}
+
+		<SnapshotClearCursor<T>>::put(end);
+		Some(end - start)
+	}
+
+	/// Compute the score of `compact` against the given snapshot `voters` and `targets`.
+	///
+	/// The winners are the unique targets of `compact`. This reads no storage, and does not check
+	/// the solution for feasibility.
+	pub fn score_compact(
+		compact: &CompactOf<T>,
+		voters: &Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>,
+		targets: &Vec<T::AccountId>,
+	) -> Result<ElectionScore, sp_npos_elections::Error> {
+		let cache = helpers::generate_voter_cache::<T>(voters);
+		let stake_of = helpers::stake_of_fn::<T>(voters, &cache);
+		let voter_at = helpers::voter_at_fn::<T>(voters);
+		let target_at = helpers::target_at_fn::<T>(targets);
+
+		let winners = compact
+			.unique_targets()
+			.into_iter()
+			.map(|i| target_at(i).ok_or(sp_npos_elections::Error::CompactInvalidIndex))
+			.collect::<Result<Vec<_>, _>>()?;
+		compact.clone().score(&winners, stake_of, voter_at, target_at)
 	}
 
 	/// Checks the feasibility of a solution.
@@ -901,26 +1698,38 @@ impl<T: Config> Pallet<T> {
 		// first, check round.
 		ensure!(Self::round() == round, FeasibilityError::InvalidRound);
 
+		// then, check that no voter lists the same target twice. This needs no storage access.
+		compact.sanity_check()?;
+
 		// winners are not directly encoded in the solution.
 		let winners = compact.unique_targets();
 
 		let desired_targets =
 			Self::desired_targets().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		ensure!(desired_targets > 0, FeasibilityError::ZeroDesiredTargets);
 
 		// NOTE: this is a bit of duplicate, but we keep it around for veracity. The unsigned path
 		// already checked this in `unsigned_per_dispatch_checks`. The signed path *could* check it
 		// upon arrival, thus we would then remove it here. Given overlay it is cheap anyhow
 		ensure!(winners.len() as u32 == desired_targets, FeasibilityError::WrongWinnerCount);
 
-		// read the entire snapshot.
-		let RoundSnapshot { voters: snapshot_voters, targets: snapshot_targets } =
-			Self::snapshot().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		// read the targets of the snapshot. The voters are only read from the pages that contain
+		// the voters of the solution.
+		let snapshot_targets =
+			Self::snapshot_targets().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		let snapshot_voters = helpers::PagedVoters::<T>::of_snapshot();
+
+		// the stake and votes of each voter of the solution, recorded as they are read.
+		let solution_voters: RefCell<BTreeMap<T::AccountId, (VoteWeight, Vec<T::AccountId>)>> =
+			Default::default();
 
 		// ----- Start building. First, we need some closures.
-		let cache = helpers::generate_voter_cache::<T>(&snapshot_voters);
-		let voter_at = helpers::voter_at_fn::<T>(&snapshot_voters);
+		let voter_at = |i: CompactVoterIndexOf<T>| -> Option<T::AccountId> {
+			let (who, stake, targets) = snapshot_voters.get_at(i)?;
+			solution_voters.borrow_mut().insert(who.clone(), (stake, targets));
+			Some(who)
+		};
 		let target_at = helpers::target_at_fn::<T>(&snapshot_targets);
-		let voter_index = helpers::voter_index_fn_usize::<T>(&cache);
 
 		// first, make sure that all the winners are sane.
 		// OPTIMIZATION: we could first build the assignments, and then extract the winners directly
@@ -932,26 +1741,32 @@ impl<T: Config> Pallet<T> {
 			.map(|i| target_at(i).ok_or(FeasibilityError::InvalidWinner))
 			.collect::<Result<Vec<T::AccountId>, FeasibilityError>>()?;
 
+		// Being in the snapshot is not enough, the winners must also be viable at this point.
+		ensure!(
+			winners.iter().all(|w| T::DataProvider::is_viable_candidate(w)),
+			FeasibilityError::NonViableWinner,
+		);
+
+		// Ensure that the solution brings some change, if needed.
+		if T::RejectUnchangedSolutions::get() {
+			let mut sorted_winners = winners.clone();
+			sorted_winners.sort();
+			ensure!(sorted_winners != Self::last_elected(), FeasibilityError::NoChange);
+		}
+
 		// Then convert compact -> assignment. This will fail if any of the indices are gibberish.
 		let assignments = compact
 			.into_assignment(voter_at, target_at)
 			.map_err::<FeasibilityError, _>(Into::into)?;
+		let solution_voters = solution_voters.into_inner();
 
 		// Ensure that assignments is correct.
 		let _ = assignments
 			.iter()
 			.map(|ref assignment| {
-				// check that assignment.who is actually a voter (defensive-only).
-				// NOTE: while using the index map from `voter_index` is better than a blind linear
-				// search, this *still* has room for optimization. Note that we had the index when
-				// we did `compact -> assignment` and we lost it. Ideal is to keep the index around.
-
-				// defensive-only: must exist in the snapshot.
-				let snapshot_index =
-					voter_index(&assignment.who).ok_or(FeasibilityError::InvalidVoter)?;
-				// defensive-only: index comes from the snapshot, must exist.
-				let (_voter, _stake, targets) =
-					snapshot_voters.get(snapshot_index).ok_or(FeasibilityError::InvalidVoter)?;
+				// defensive-only: every voter of the assignments was read from the snapshot.
+				let (_stake, targets) =
+					solution_voters.get(&assignment.who).ok_or(FeasibilityError::InvalidVoter)?;
 
 				// check that all of the targets are valid based on the snapshot.
 				if assignment.distribution.iter().any(|(d, _)| !targets.contains(d)) {
@@ -962,7 +1777,9 @@ impl<T: Config> Pallet<T> {
 			.collect::<Result<(), FeasibilityError>>()?;
 
 		// ----- Start building support. First, we need one more closure.
-		let stake_of = helpers::stake_of_fn::<T>(&snapshot_voters, &cache);
+		let stake_of = |who: &T::AccountId| -> VoteWeight {
+			solution_voters.get(who).map_or(0, |(stake, _)| *stake)
+		};
 
 		// This might fail if the normalization fails. Very unlikely. See `integrity_test`.
 		let staked_assignments = assignment_ratio_to_staked_normalized(assignments, stake_of)
@@ -973,27 +1790,83 @@ impl<T: Config> Pallet<T> {
 		let supports = sp_npos_elections::to_supports(&winners, &staked_assignments)
 			.map_err::<FeasibilityError, _>(Into::into)?;
 
+		// Check that each winner is self-backed with enough stake, if needed.
+		let min_self_stake = T::MinSelfStake::get() as ExtendedBalance;
+		if !min_self_stake.is_zero() {
+			let all_self_staked = supports.iter().all(|(winner, support)| {
+				support
+					.voters
+					.iter()
+					.find(|(voter, _)| voter == winner)
+					.map_or(false, |(_, stake)| *stake >= min_self_stake)
+			});
+			ensure!(all_self_staked, FeasibilityError::InsufficientSelfStake);
+		}
+
+		// Check that each winner has enough distinct backers. The supports are built from the
+		// assignments, which contain each voter only once, hence no need to deduplicate here.
+		let min_backers = T::MinBackersPerWinner::get() as usize;
+		ensure!(
+			supports.iter().all(|(_, support)| support.voters.len() >= min_backers),
+			FeasibilityError::InsufficientBackers,
+		);
+
+		// Check that each winner is backed with enough stake in total.
+		let min_support = T::MinWinnerSupport::get();
+		ensure!(
+			supports.iter().all(|(_, support)| support.total >= min_support),
+			FeasibilityError::InsufficientWinnerSupport,
+		);
+
 		// Finally, check that the claimed score was indeed correct.
 		let known_score = (&supports).evaluate();
 		ensure!(known_score == score, FeasibilityError::InvalidScore);
 
-		Ok(ReadySolution { supports, compute, score })
+		Ok(ReadySolution { supports, compute, score, round })
 	}
 
 	/// Perform the tasks to be done after a new `elect` has been triggered:
 	///
 	/// 1. Increment round.
 	/// 2. Change phase to [`Phase::Off`]
-	/// 3. Clear all snapshot data.
+	/// 3. Clear all snapshot data, or start to, see [`Config::SnapshotClearPagesPerBlock`].
+	/// 4. Record the block number in [`LastElectBlock`].
+	/// 5. Clear [`LastUnsignedAt`].
 	fn post_elect() {
 		// inc round
 		<Round<T>>::mutate(|r| *r = *r + 1);
 
+		// record when this happened.
+		let now = <frame_system::Pallet<T>>::block_number();
+		<LastElectBlock<T>>::put(now);
+		log!(info, "Round {} completed at #{:?}.", Self::round() - 1, now);
+
 		// change phase
 		<CurrentPhase<T>>::put(Phase::Off);
 
 		// kill snapshots
 		Self::kill_snapshot();
+
+		// the next round's unsigned submissions are not rate-limited by this one.
+		<LastUnsignedAt<T>>::kill();
+
+		// the next round makes its own prediction.
+		<PredictedElectionBlock<T>>::kill();
+	}
+
+	/// Record the compute of the current round in [`ComputeHistory`], evicting the entry that
+	/// falls out of [`Config::ComputeHistoryDepth`], if any.
+	fn record_compute(compute: ElectionCompute) {
+		let depth = T::ComputeHistoryDepth::get();
+		if depth.is_zero() {
+			return;
+		}
+
+		let round = Self::round();
+		<ComputeHistory<T>>::insert(round, compute);
+		if let Some(evicted) = round.checked_sub(depth) {
+			<ComputeHistory<T>>::remove(evicted);
+		}
 	}
 
 	/// On-chain fallback of election.
@@ -1005,24 +1878,70 @@ impl<T: Config> Pallet<T> {
 		.map_err(Into::into)
 	}
 
-	fn do_elect() -> Result<Supports<T::AccountId>, ElectionError> {
-		<QueuedSolution<T>>::take()
-			.map_or_else(
-				|| match T::Fallback::get() {
-					FallbackStrategy::OnChain => Self::onchain_fallback()
-						.map(|r| (r, ElectionCompute::OnChain))
-						.map_err(Into::into),
-					FallbackStrategy::Nothing => Err(ElectionError::NoFallbackConfigured),
-				},
-				|ReadySolution { supports, compute, .. }| Ok((supports, compute)),
-			)
+	/// Same as [`ElectionProvider::elect`], but also returns the [`ElectionCompute`] that
+	/// produced the result.
+	///
+	/// This can be used by a runtime that wishes to record the provenance of each election
+	/// outcome, e.g. to detect the fallback being repeatedly hit.
+	///
+	/// A queued solution that was computed for another round than the current one is ignored, and
+	/// the fallback is used instead.
+	pub fn elect_with_compute() -> Result<(Supports<T::AccountId>, ElectionCompute), ElectionError>
+	{
+		let outcome = Self::do_elect();
+		Self::post_elect();
+		T::OnPhaseTransition::on_elect();
+		outcome
+	}
+
+	fn do_elect() -> Result<(Supports<T::AccountId>, ElectionCompute), ElectionError> {
+		<QueuedSolutionScore<T>>::kill();
+		let queued = if <ForceFallback<T>>::take() {
+			log!(warn, "Fallback is forced, ignoring any queued solution.");
+			<QueuedSolution<T>>::kill();
+			None
+		} else {
+			<QueuedSolution<T>>::take().filter(|ready| {
+				let current = Self::round();
+				if ready.round != current {
+					log!(
+						warn,
+						"queued solution is from round {} while in round {}, ignoring.",
+						ready.round,
+						current,
+					);
+				}
+				ready.round == current
+			})
+		};
+
+		queued
+			.map_or_else(
+				|| match T::Fallback::get() {
+					FallbackStrategy::OnChain => Self::onchain_fallback()
+						.map(|r| (r, ElectionCompute::OnChain))
+						.map_err(Into::into),
+					FallbackStrategy::Nothing => Err(ElectionError::NoFallbackConfigured),
+				},
+				|ReadySolution { supports, compute, .. }| Ok((supports, compute)),
+			)
 			.map(|(supports, compute)| {
-				Self::deposit_event(Event::ElectionFinalized(Some(compute)));
+				let winner_count = supports.len() as u32;
+				if T::RejectUnchangedSolutions::get() {
+					let mut winners = supports.iter().map(|(w, _)| w.clone()).collect::<Vec<_>>();
+					winners.sort();
+					<LastElected<T>>::put(winners);
+				}
+				<LastWinnerCount<T>>::put(winner_count);
+				Self::record_compute(compute);
+
+				Self::deposit_event(Event::ElectionFinalized(Some(compute), winner_count));
 				log!(info, "Finalized election round with compute {:?}.", compute);
-				supports
+				(supports, compute)
 			})
 			.map_err(|err| {
-				Self::deposit_event(Event::ElectionFinalized(None));
+				<LastWinnerCount<T>>::put(0);
+				Self::deposit_event(Event::ElectionFinalized(None, 0));
 				log!(warn, "Failed to finalize election round. reason {:?}", err);
 				err
 			})
@@ -1034,9 +1953,33 @@ impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for Pallet<T> {
 	type DataProvider = T::DataProvider;
 
 	fn elect() -> Result<Supports<T::AccountId>, Self::Error> {
-		let outcome = Self::do_elect();
-		Self::post_elect();
-		outcome
+		Self::elect_with_compute().map(|(supports, _)| supports)
+	}
+
+	fn ongoing() -> bool {
+		!Self::current_phase().is_off()
+	}
+
+	/// The solution is computed ahead of time, thus enacting a queued solution only costs the
+	/// storage bookkeeping of the round. The worst case is thus falling back to an on-chain
+	/// election, if [`Config::Fallback`] allows it.
+	fn estimate_cost(voters: u32, targets: u32) -> Weight {
+		// reads of `ForceFallback`, `QueuedSolution` and `Round`, and writes to them and every item
+		// of the snapshot, including one per page of voters, unless they are cleared later on.
+		let pages = match T::SnapshotClearPagesPerBlock::get() {
+			0 => voters / T::SnapshotPageSize::get().max(1) + 1,
+			_ => 1,
+		};
+		let bookkeeping = T::DbWeight::get().reads_writes(3, 11 + pages as Weight);
+		let fallback = match T::Fallback::get() {
+			FallbackStrategy::OnChain =>
+				onchain::OnChainSequentialPhragmen::<OnChainConfig<T>>::estimate_cost(
+					voters,
+					targets,
+				),
+			FallbackStrategy::Nothing => 0,
+		};
+		bookkeeping.saturating_add(fallback)
 	}
 }
 
@@ -1068,7 +2011,7 @@ mod feasibility_check {
 			let solution = raw_solution();
 
 			// for whatever reason it might be:
-			<Snapshot<Runtime>>::kill();
+			<SnapshotTargets<Runtime>>::kill();
 
 			assert_noop!(
 				MultiPhase::feasibility_check(solution, COMPUTE),
@@ -1098,6 +2041,10 @@ mod feasibility_check {
 			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
 			assert!(MultiPhase::current_phase().is_signed());
 
+			// the snapshot clamps this to the number of targets, so we force it.
+			assert_eq!(MultiPhase::desired_targets().unwrap(), 4);
+			super::DesiredTargets::<Runtime>::put(8);
+
 			let solution = raw_solution();
 
 			assert_eq!(solution.compact.unique_targets().len(), 4);
@@ -1110,6 +2057,24 @@ mod feasibility_check {
 		})
 	}
 
+	#[test]
+	fn zero_desired_targets() {
+		ExtBuilder::default().desired_targets(0).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::desired_targets().unwrap(), 0);
+
+			// an empty solution has the correct number of winners, yet it is not feasible.
+			let solution = RawSolution::<TestCompact>::default();
+			assert_eq!(solution.compact.unique_targets().len(), 0);
+
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::ZeroDesiredTargets,
+			);
+		})
+	}
+
 	#[test]
 	fn winner_indices() {
 		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
@@ -1144,6 +2109,24 @@ mod feasibility_check {
 		})
 	}
 
+	#[test]
+	fn winner_viability() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let solution = raw_solution();
+			assert_ok!(MultiPhase::feasibility_check(solution.clone(), COMPUTE));
+
+			// 40 is still in the snapshot, but no longer viable.
+			<NonViableTargets>::set(vec![40]);
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::NonViableWinner,
+			);
+		})
+	}
+
 	#[test]
 	fn voter_indices() {
 		// should be caught in `compact.into_assignment`.
@@ -1172,6 +2155,22 @@ mod feasibility_check {
 		})
 	}
 
+	#[test]
+	fn duplicate_targets_are_rejected() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// voter 3 (4) splits their vote between 3 (40) and... 3 (40) again.
+			let mut solution = raw_solution();
+			solution.compact.votes2.push((3, (3, sp_runtime::PerU16::from_percent(50)), 3));
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::NposElection(sp_npos_elections::Error::CompactDuplicateTarget),
+			);
+		})
+	}
+
 	#[test]
 	fn voter_votes() {
 		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
@@ -1219,11 +2218,111 @@ mod feasibility_check {
 			);
 		})
 	}
+
+	#[test]
+	fn self_stake() {
+		ExtBuilder::default().min_self_stake(35).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// winners are 30 and 40, self-backed with 30 and 40 respectively.
+			let solution = raw_solution();
+			assert_noop!(
+				MultiPhase::feasibility_check(solution.clone(), COMPUTE),
+				FeasibilityError::InsufficientSelfStake,
+			);
+
+			<MinSelfStake>::set(30);
+			assert_ok!(MultiPhase::feasibility_check(solution, COMPUTE));
+		})
+	}
+
+	#[test]
+	fn reject_unchanged_solutions() {
+		ExtBuilder::default().reject_unchanged(true).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::last_elected().is_empty());
+
+			// nothing elected so far, thus any solution is a change.
+			let solution = raw_solution();
+			assert_ok!(MultiPhase::feasibility_check(solution, COMPUTE));
+
+			// elect, then try the same winners again in the next round.
+			let _ = MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_elected(), vec![30, 40]);
+
+			roll_to(<EpochLength>::get() * 2 - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let solution = raw_solution();
+			assert_noop!(
+				MultiPhase::feasibility_check(solution.clone(), COMPUTE),
+				FeasibilityError::NoChange,
+			);
+
+			// had the last election been different, this is fine.
+			<LastElected<Runtime>>::put(vec![10, 40]);
+			assert_ok!(MultiPhase::feasibility_check(solution.clone(), COMPUTE));
+
+			// and without the check, it is fine too.
+			<LastElected<Runtime>>::put(vec![30, 40]);
+			<RejectUnchangedSolutions>::set(false);
+			assert_ok!(MultiPhase::feasibility_check(solution, COMPUTE));
+		})
+	}
+
+	#[test]
+	fn last_elected_not_kept_without_reject_unchanged() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let _ = MultiPhase::elect().unwrap();
+			assert!(MultiPhase::last_elected().is_empty());
+		})
+	}
+
+	#[test]
+	fn min_backers() {
+		ExtBuilder::default().min_backers(4).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// winner 30 is backed by 3 voters, 40 by 4.
+			let solution = raw_solution();
+			assert_noop!(
+				MultiPhase::feasibility_check(solution.clone(), COMPUTE),
+				FeasibilityError::InsufficientBackers,
+			);
+
+			<MinBackersPerWinner>::set(3);
+			assert_ok!(MultiPhase::feasibility_check(solution, COMPUTE));
+		})
+	}
+
+	#[test]
+	fn min_winner_support() {
+		ExtBuilder::default().min_winner_support(50).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// winner 30 is backed by 40 in total, 40 by 60.
+			let solution = raw_solution();
+			assert_noop!(
+				MultiPhase::feasibility_check(solution.clone(), COMPUTE),
+				FeasibilityError::InsufficientWinnerSupport,
+			);
+
+			<MinWinnerSupport>::set(40);
+			assert_ok!(MultiPhase::feasibility_check(solution, COMPUTE));
+		})
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{mock::*, Event, *};
+	use super::{mock::{Origin, *}, Event, *};
 	use sp_election_providers::ElectionProvider;
 	use sp_npos_elections::Support;
 
@@ -1293,54 +2392,235 @@ mod tests {
 	}
 
 	#[test]
-	fn signed_phase_void() {
-		ExtBuilder::default().phases(0, 10).build_and_execute(|| {
+	fn last_elect_block_is_recorded() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(MultiPhase::last_elect_block().is_none());
+
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(MultiPhase::last_elect_block().is_none());
+
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_elect_block(), Some(30));
+
+			// not touched by the next phases, only by the next `elect`.
+			roll_to(55);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::last_elect_block(), Some(30));
+
+			roll_to(60);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_elect_block(), Some(60));
+		})
+	}
+
+	#[test]
+	fn phase_transition_hooks_work() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(14);
+			assert!(PhaseTransitions::get().is_empty());
+
+			roll_to(15);
+			assert_eq!(PhaseTransitions::get(), vec!["signed"]);
+
+			roll_to(25);
+			assert_eq!(PhaseTransitions::get(), vec!["signed", "unsigned"]);
+
+			// called even if the election fails.
+			roll_to(30);
+			let _ = MultiPhase::elect();
+			assert_eq!(PhaseTransitions::get(), vec!["signed", "unsigned", "elect"]);
+		})
+	}
+
+	#[test]
+	fn phases_are_not_opened_if_snapshot_too_large() {
+		ExtBuilder::default().max_snapshot_size(7, 0).build_and_execute(|| {
 			roll_to(15);
 			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+			assert_eq!(multi_phase_events(), vec![Event::SnapshotTooLarge(Some(8), Some(4))]);
 
-			roll_to(19);
+			// nor the unsigned phase.
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+		});
+
+		ExtBuilder::default().max_snapshot_size(0, 3).build_and_execute(|| {
+			roll_to(15);
 			assert!(MultiPhase::current_phase().is_off());
+		});
 
-			roll_to(20);
-			assert!(MultiPhase::current_phase().is_unsigned_open_at(20));
+		// exactly at the limit is fine.
+		ExtBuilder::default().max_snapshot_size(8, 4).build_and_execute(|| {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
 			assert!(MultiPhase::snapshot().is_some());
+		});
+	}
 
+	#[test]
+	fn election_gap_delays_next_phase() {
+		ExtBuilder::default().election_gap(20).build_and_execute(|| {
 			roll_to(30);
-			assert!(MultiPhase::current_phase().is_unsigned_open_at(20));
-
 			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_elect_block(), Some(30));
 
+			// the signed phase of the next round would have opened at 45.
+			roll_to(49);
 			assert!(MultiPhase::current_phase().is_off());
-			assert!(MultiPhase::snapshot().is_none());
-		});
+
+			roll_to(50);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::snapshot().is_some());
+		})
 	}
 
 	#[test]
-	fn unsigned_phase_void() {
-		ExtBuilder::default().phases(10, 0).build_and_execute(|| {
+	fn snapshot_is_taken_ahead_of_time() {
+		let full_snapshot = ExtBuilder::default().build().execute_with(|| {
 			roll_to(15);
+			MultiPhase::snapshot().unwrap()
+		});
+
+		// 8 voters in pages of 3 take three blocks, starting at 12.
+		ExtBuilder::default().snapshot_delay(3).build_and_execute(|| {
+			roll_to(11);
+			assert!(MultiPhase::snapshot().is_none());
+
+			roll_to(12);
+			assert!(MultiPhase::current_phase().is_off());
+			assert_eq!(MultiPhase::snapshot_in_progress(), Some(1));
+			assert_eq!(MultiPhase::desired_targets(), Some(2));
+			assert!(MultiPhase::snapshot_metadata().is_none());
+
+			roll_to(13);
+			assert_eq!(MultiPhase::snapshot_in_progress(), Some(2));
+
+			roll_to(14);
 			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot_in_progress().is_none());
+			assert_eq!(
+				MultiPhase::snapshot_metadata(),
+				Some(SolutionOrSnapshotSize { voters: 8, targets: 4 })
+			);
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(14));
+
+			// the complete snapshot is used as-is once the signed phase opens.
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(14));
+			assert_eq!(MultiPhase::snapshot(), Some(full_snapshot.clone()));
+		});
+
+		// not enough blocks to take all the pages: the snapshot is taken at once.
+		ExtBuilder::default().snapshot_delay(2).build_and_execute(|| {
+			roll_to(14);
+			assert_eq!(MultiPhase::snapshot_in_progress(), Some(2));
+			assert!(MultiPhase::snapshot_metadata().is_none());
 
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::snapshot_in_progress().is_none());
+			assert_eq!(
+				MultiPhase::snapshot_metadata(),
+				Some(SolutionOrSnapshotSize { voters: 8, targets: 4 })
+			);
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(15));
+			assert_eq!(MultiPhase::snapshot(), Some(full_snapshot.clone()));
+		});
+
+		// without a signed phase, the snapshot is complete by the time the unsigned phase opens.
+		ExtBuilder::default().phases(0, 10).snapshot_delay(3).build_and_execute(|| {
 			roll_to(19);
 			assert!(MultiPhase::current_phase().is_off());
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(19));
 
 			roll_to(20);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(19));
+			assert_eq!(MultiPhase::snapshot(), Some(full_snapshot));
+		});
+
+		// the snapshot taken ahead of time is cleared by `elect`.
+		ExtBuilder::default().snapshot_delay(3).build_and_execute(|| {
+			roll_to(13);
+			assert!(MultiPhase::snapshot_in_progress().is_some());
+			let _ = MultiPhase::elect();
+			assert!(MultiPhase::snapshot_in_progress().is_none());
+			assert!(MultiPhase::snapshot().is_none());
+		});
+	}
+
+	#[test]
+	fn snapshot_is_cleared_in_chunks() {
+		let builder = (100..120).fold(ExtBuilder::default(), |builder, who| {
+			builder.add_voter(who, 10, vec![10])
+		});
+		builder.snapshot_page_size(2).snapshot_clear_pages_per_block(4).build_and_execute(|| {
+			roll_to(25);
+			assert_eq!(MultiPhase::snapshot_metadata().unwrap().voters, 28);
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 14);
+
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::snapshot().is_none());
+			assert_eq!(MultiPhase::snapshot_clear_cursor(), Some(0));
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 14);
+
+			roll_to(31);
+			assert_eq!(MultiPhase::snapshot_clear_cursor(), Some(4));
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 10);
+
+			roll_to(33);
+			assert_eq!(MultiPhase::snapshot_clear_cursor(), Some(12));
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 2);
+
+			// the last two pages, and the end of the snapshot.
+			roll_to(34);
+			assert!(MultiPhase::snapshot_clear_cursor().is_none());
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 0);
+
+			// the next round is not affected.
+			roll_to(45);
 			assert!(MultiPhase::current_phase().is_signed());
-			assert!(MultiPhase::snapshot().is_some());
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 14);
+		});
 
+		// by default, all the pages are removed at once.
+		ExtBuilder::default().snapshot_page_size(2).build_and_execute(|| {
 			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::snapshot_clear_cursor().is_none());
+			assert_eq!(SnapshotVoters::<Runtime>::iter().count(), 0);
+		});
+	}
+
+	#[test]
+	fn ongoing_follows_phase() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(14);
+			assert!(!MultiPhase::ongoing());
+
+			roll_to(15);
 			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::ongoing());
 
-			let _ = MultiPhase::elect().unwrap();
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(MultiPhase::ongoing());
 
-			assert!(MultiPhase::current_phase().is_off());
-			assert!(MultiPhase::snapshot().is_none());
-		});
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert!(!MultiPhase::ongoing());
+		})
 	}
 
 	#[test]
-	fn both_phases_void() {
-		ExtBuilder::default().phases(0, 0).build_and_execute(|| {
+	fn signed_phase_void() {
+		ExtBuilder::default().phases(0, 10).build_and_execute(|| {
 			roll_to(15);
 			assert!(MultiPhase::current_phase().is_off());
 
@@ -1348,13 +2628,273 @@ mod tests {
 			assert!(MultiPhase::current_phase().is_off());
 
 			roll_to(20);
-			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::current_phase().is_unsigned_open_at(20));
+			assert!(MultiPhase::snapshot().is_some());
 
 			roll_to(30);
-			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::current_phase().is_unsigned_open_at(20));
+			assert!(!multi_phase_events().contains(&Event::SignedPhaseStarted(1)));
 
-			// this module is now only capable of doing on-chain backup.
-			let _ = MultiPhase::elect().unwrap();
+			MultiPhase::elect().unwrap();
+
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+		});
+	}
+
+	#[test]
+	fn unsigned_phase_void() {
+		ExtBuilder::default().phases(10, 0).build_and_execute(|| {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_off());
+
+			roll_to(19);
+			assert!(MultiPhase::current_phase().is_off());
+
+			roll_to(20);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::snapshot().is_some());
+
+			roll_to(30);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// nothing could have been queued, the fallback is used.
+			let _ = MultiPhase::elect().unwrap();
+			assert_eq!(
+				multi_phase_events().last().unwrap(),
+				&Event::ElectionFinalized(Some(ElectionCompute::OnChain), 2),
+			);
+
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+		});
+	}
+
+	#[test]
+	fn phase_predicates_work() {
+		let off = Phase::<u64>::Off;
+		assert!(off.is_off());
+		assert!(!off.is_signed());
+		assert!(!off.is_unsigned());
+		assert!(!off.is_unsigned_open());
+		assert!(!off.is_unsigned_open_at(0));
+
+		let signed = Phase::<u64>::Signed;
+		assert!(!signed.is_off());
+		assert!(signed.is_signed());
+		assert!(!signed.is_unsigned());
+		assert!(!signed.is_unsigned_open());
+		assert!(!signed.is_unsigned_open_at(0));
+
+		let open = Phase::<u64>::Unsigned((true, 5));
+		assert!(!open.is_off());
+		assert!(!open.is_signed());
+		assert!(open.is_unsigned());
+		assert!(open.is_unsigned_open());
+		assert!(open.is_unsigned_open_at(5));
+		assert!(!open.is_unsigned_open_at(6));
+
+		let closed = Phase::<u64>::Unsigned((false, 5));
+		assert!(!closed.is_off());
+		assert!(!closed.is_signed());
+		assert!(closed.is_unsigned());
+		assert!(!closed.is_unsigned_open());
+		assert!(!closed.is_unsigned_open_at(5));
+	}
+
+	#[test]
+	fn phase_remaining_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(MultiPhase::phase_remaining(5), None);
+
+			// signed phase is [15, 25), the election at 30.
+			roll_to(15);
+			assert_eq!(MultiPhase::phase_remaining(15), Some(10));
+			roll_to(24);
+			assert_eq!(MultiPhase::phase_remaining(24), Some(1));
+
+			roll_to(25);
+			assert_eq!(MultiPhase::phase_remaining(25), Some(5));
+			roll_to(30);
+			assert_eq!(MultiPhase::phase_remaining(30), Some(0));
+
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::phase_remaining(30), None);
+		});
+
+		// a stale prediction re-schedules the election, the signed phase lasts as usual.
+		ExtBuilder::default().build_and_execute(|| {
+			<StalePrediction>::set(true);
+			roll_to(5);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::predicted_election_block(), Some(16));
+			assert_eq!(MultiPhase::phase_remaining(5), Some(6));
+		});
+	}
+
+	#[test]
+	fn unsigned_phase_is_active_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(MultiPhase::unsigned_phase_is_active(), None);
+
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::unsigned_phase_is_active(), None);
+
+			// the signed phase did not produce anything, unsigned phase is active.
+			roll_to(25);
+			assert_eq!(MultiPhase::unsigned_phase_is_active(), Some(true));
+
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::unsigned_phase_is_active(), None);
+
+			// a signed phase that yields a solution leads to a passive unsigned phase.
+			<CurrentPhase<Runtime>>::put(Phase::Unsigned((false, 55)));
+			assert_eq!(MultiPhase::unsigned_phase_is_active(), Some(false));
+		});
+	}
+
+	#[test]
+	fn unsigned_phase_mode_works() {
+		let queue_during_signed = || {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_ok!(MultiPhase::set_emergency_solution(Origin::root(), Default::default()));
+			roll_to(25);
+		};
+
+		ExtBuilder::default().build_and_execute(|| {
+			queue_during_signed();
+			assert_eq!(MultiPhase::unsigned_phase_is_active(), Some(true));
+		});
+
+		ExtBuilder::default()
+			.unsigned_phase_mode(crate::UnsignedPhaseMode::ActiveOnlyAfterSignedFailure)
+			.build_and_execute(|| {
+				queue_during_signed();
+				assert_eq!(MultiPhase::unsigned_phase_is_active(), Some(false));
+
+				// no unsigned solution is accepted anymore.
+				let (solution, _) = MultiPhase::mine_solution(2, true).unwrap();
+				assert_noop!(
+					MultiPhase::unsigned_pre_dispatch_checks(&solution),
+					Error::<Runtime>::PreDispatchEarlySubmission,
+				);
+			});
+
+		// nothing queued by the signed phase.
+		ExtBuilder::default()
+			.unsigned_phase_mode(crate::UnsignedPhaseMode::ActiveOnlyAfterSignedFailure)
+			.build_and_execute(|| {
+				roll_to(25);
+				assert_eq!(MultiPhase::unsigned_phase_is_active(), Some(true));
+			});
+
+		// no signed phase at all.
+		ExtBuilder::default()
+			.phases(0, 5)
+			.unsigned_phase_mode(crate::UnsignedPhaseMode::ActiveOnlyAfterSignedFailure)
+			.build_and_execute(|| {
+				roll_to(25);
+				assert_eq!(MultiPhase::unsigned_phase_is_active(), Some(true));
+			});
+	}
+
+	#[test]
+	fn stale_prediction_opens_signed_phase() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(5);
+			assert!(MultiPhase::current_phase().is_off());
+
+			<StalePrediction>::set(true);
+			roll_to(6);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::snapshot().is_some());
+			assert_eq!(
+				multi_phase_events(),
+				vec![Event::PhaseTransitionFailed(5), Event::SignedPhaseStarted(1)],
+			);
+
+			// the election is re-scheduled, and the phases follow as usual.
+			assert_eq!(MultiPhase::predicted_election_block(), Some(21));
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(multi_phase_events().len(), 2);
+			roll_to(16);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 16)));
+		});
+
+		// a prediction of the current block is not stale.
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(5);
+			<PredictedElectionBlock<Runtime>>::put(6);
+			roll_to(6);
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(multi_phase_events().is_empty());
+		});
+
+		// without a signed phase, the unsigned phase is opened instead.
+		ExtBuilder::default().phases(0, 10).build_and_execute(|| {
+			<StalePrediction>::set(true);
+			roll_to(6);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 1)));
+			assert!(MultiPhase::snapshot().is_some());
+			assert_eq!(MultiPhase::predicted_election_block(), Some(11));
+		});
+
+		// without any phase, nothing is opened.
+		ExtBuilder::default().phases(0, 0).build_and_execute(|| {
+			<StalePrediction>::set(true);
+			roll_to(6);
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(multi_phase_events().is_empty());
+		});
+	}
+
+	#[test]
+	fn prediction_is_latched_for_the_round() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(14);
+			assert!(MultiPhase::predicted_election_block().is_none());
+
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::predicted_election_block(), Some(30));
+
+			// the prediction jitters, but the unsigned phase still opens on time.
+			<PredictionJitter>::set(6);
+			roll_to(25);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 25)));
+			assert_eq!(MultiPhase::predicted_election_block(), Some(30));
+
+			// the latch is reset upon elect, the next round uses a fresh prediction.
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::predicted_election_block().is_none());
+
+			roll_to(51);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::predicted_election_block(), Some(66));
+		})
+	}
+
+	#[test]
+	fn both_phases_void() {
+		ExtBuilder::default().phases(0, 0).build_and_execute(|| {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_off());
+
+			roll_to(19);
+			assert!(MultiPhase::current_phase().is_off());
+
+			roll_to(20);
+			assert!(MultiPhase::current_phase().is_off());
+
+			roll_to(30);
+			assert!(MultiPhase::current_phase().is_off());
+
+			// this module is now only capable of doing on-chain backup.
+			let _ = MultiPhase::elect().unwrap();
 
 			assert!(MultiPhase::current_phase().is_off());
 		});
@@ -1382,7 +2922,7 @@ mod tests {
 				multi_phase_events(),
 				vec![
 					Event::SignedPhaseStarted(1),
-					Event::ElectionFinalized(Some(ElectionCompute::OnChain))
+					Event::ElectionFinalized(Some(ElectionCompute::OnChain), 2)
 				],
 			);
 			// all storage items must be cleared.
@@ -1394,6 +2934,97 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn snapshot_applies_voter_weight_adjustment() {
+		ExtBuilder::default().build_and_execute(|| {
+			<Convictions>::set(vec![(1, 3), (30, 2)]);
+
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let voters = MultiPhase::snapshot().unwrap().voters;
+			let stake_of = |who: AccountId| voters.iter().find(|(v, _, _)| *v == who).unwrap().1;
+			assert_eq!(stake_of(1), 30);
+			assert_eq!(stake_of(30), 60);
+			// others are untouched.
+			assert_eq!(stake_of(2), 10);
+			assert_eq!(stake_of(40), 40);
+		})
+	}
+
+	#[test]
+	fn snapshot_clamps_desired_targets() {
+		ExtBuilder::default().desired_targets(4).build_and_execute(|| {
+			<Targets>::set(vec![30, 40]);
+
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::snapshot().unwrap().targets.len(), 2);
+			assert_eq!(MultiPhase::desired_targets(), Some(2));
+			assert_eq!(
+				multi_phase_events(),
+				vec![Event::DesiredTargetsAdjusted(4, 2), Event::SignedPhaseStarted(1)],
+			);
+		})
+	}
+
+	#[test]
+	fn snapshot_is_cleared_after_full_round() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(MultiPhase::snapshot().is_some());
+			assert!(MultiPhase::snapshot_metadata().is_some());
+			assert!(MultiPhase::desired_targets().is_some());
+			// created upon the start of the signed phase.
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(15));
+
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+
+			roll_to(30);
+			let _ = MultiPhase::elect().unwrap();
+			assert_eq!(
+				multi_phase_events().last().unwrap(),
+				&Event::ElectionFinalized(Some(ElectionCompute::Unsigned), 2),
+			);
+
+			// all storage items must be cleared.
+			assert!(MultiPhase::snapshot().is_none());
+			assert!(MultiPhase::snapshot_metadata().is_none());
+			assert!(MultiPhase::desired_targets().is_none());
+			assert!(MultiPhase::snapshot_created_at().is_none());
+			assert!(MultiPhase::queued_solution().is_none());
+			assert!(MultiPhase::queued_solution_score().is_none());
+			assert_eq!(MultiPhase::snapshot_voter_pages().count(), 0);
+		})
+	}
+
+	#[test]
+	fn snapshot_voters_are_paged() {
+		ExtBuilder::default().snapshot_page_size(3).build_and_execute(|| {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// 8 voters, in pages of 3.
+			assert_eq!(
+				MultiPhase::snapshot_voter_pages().map(|p| p.len()).collect::<Vec<_>>(),
+				vec![3, 3, 2],
+			);
+			assert_eq!(
+				MultiPhase::snapshot().unwrap(),
+				RoundSnapshot { voters: Voters::get(), targets: Targets::get() },
+			);
+		});
+
+		// zero is treated as one.
+		ExtBuilder::default().snapshot_page_size(0).build_and_execute(|| {
+			roll_to(15);
+			assert_eq!(MultiPhase::snapshot_voter_pages().count(), 8);
+			assert_eq!(MultiPhase::snapshot().unwrap().voters, Voters::get());
+		})
+	}
+
 	#[test]
 	fn fallback_strategy_works() {
 		ExtBuilder::default().fallabck(FallbackStrategy::OnChain).build_and_execute(|| {
@@ -1427,6 +3058,346 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn set_emergency_solution_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let supports = vec![(30, Support { total: 40, voters: vec![(2, 5), (30, 35)] })];
+			let solution = ReadySolution {
+				supports: supports.clone(),
+				// bogus, recomputed from the supports.
+				score: [1, 1, 1],
+				compute: ElectionCompute::Unsigned,
+				round: 0,
+			};
+
+			assert_noop!(
+				MultiPhase::set_emergency_solution(Origin::signed(99), solution.clone()),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(MultiPhase::set_emergency_solution(Origin::root(), solution));
+			assert_eq!(multi_phase_events().last().unwrap(), &Event::EmergencySolutionStored);
+
+			// not checked against the snapshot nor the desired targets, but tagged as such.
+			let queued = MultiPhase::queued_solution().unwrap();
+			assert_eq!(queued.compute, ElectionCompute::Emergency);
+			assert_eq!(queued.round, 1);
+			assert_eq!(MultiPhase::queued_solution_score(), Some([40, 40, 1600]));
+
+			assert_eq!(
+				MultiPhase::elect_with_compute().unwrap(),
+				(supports, ElectionCompute::Emergency),
+			);
+		})
+	}
+
+	#[test]
+	fn abort_election_works() {
+		ExtBuilder::default().snapshot_clear_pages_per_block(16).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// only root.
+			assert_noop!(MultiPhase::abort_election(Origin::signed(99)), DispatchError::BadOrigin);
+
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			assert!(MultiPhase::queued_solution().is_some());
+
+			assert_ok!(MultiPhase::abort_election(Origin::root()));
+			assert_eq!(multi_phase_events().last().unwrap(), &Event::ElectionAborted(1));
+			assert!(MultiPhase::current_phase().is_off());
+			assert_eq!(MultiPhase::round(), 2);
+			assert!(MultiPhase::queued_solution().is_none());
+			assert!(MultiPhase::queued_solution_score().is_none());
+			assert!(MultiPhase::snapshot().is_none());
+			assert!(MultiPhase::desired_targets().is_none());
+
+			// the voter pages are left to be cleared in the next block..
+			assert_eq!(MultiPhase::snapshot_clear_cursor(), Some(0));
+			assert!(MultiPhase::snapshot_voter_pages().next().is_some());
+			roll_to(26);
+			assert!(MultiPhase::snapshot_clear_cursor().is_none());
+			assert!(MultiPhase::snapshot_voter_pages().next().is_none());
+			assert!(MultiPhase::current_phase().is_off());
+
+			// ..after which the next phase is opened with a fresh snapshot.
+			roll_to(27);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 27)));
+			assert!(MultiPhase::snapshot().is_some());
+			assert_eq!(MultiPhase::snapshot_created_at(), Some(27));
+			assert_eq!(multi_phase_events().last().unwrap(), &Event::UnsignedPhaseStarted(2));
+		})
+	}
+
+	#[test]
+	fn force_fallback_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// only root.
+			assert_noop!(
+				MultiPhase::set_force_fallback(Origin::signed(99)),
+				DispatchError::BadOrigin,
+			);
+
+			// queue a solution, then force the fallback.
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			assert!(MultiPhase::queued_solution().is_some());
+
+			assert_ok!(MultiPhase::set_force_fallback(Origin::root()));
+			assert!(MultiPhase::force_fallback());
+
+			MultiPhase::elect().unwrap();
+			assert_eq!(
+				multi_phase_events().last().unwrap(),
+				&Event::ElectionFinalized(Some(ElectionCompute::OnChain), 2),
+			);
+			assert!(MultiPhase::queued_solution().is_none());
+			// consumed.
+			assert!(!MultiPhase::force_fallback());
+		})
+	}
+
+	#[test]
+	fn feasibility_check_dry_run_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let solution = raw_solution();
+			let score = solution.score;
+
+			// only signed.
+			assert_noop!(
+				MultiPhase::feasibility_check_dry_run(Origin::none(), solution.clone(), witness()),
+				DispatchError::BadOrigin,
+			);
+
+			let outcome = MultiPhase::feasibility_check_dry_run(
+				Origin::signed(99),
+				solution.clone(),
+				witness(),
+			)
+			.unwrap();
+			assert_eq!(outcome.pays_fee, frame_support::weights::Pays::Yes);
+			assert_eq!(multi_phase_events().last().unwrap(), &Event::FeasibilityDryRun(99, score));
+			// nothing is stored.
+			assert!(MultiPhase::queued_solution().is_none());
+
+			// infeasible solutions are reported.
+			let mut infeasible = solution.clone();
+			infeasible.score[0] += 1;
+			assert_noop!(
+				MultiPhase::feasibility_check_dry_run(Origin::signed(99), infeasible, witness()),
+				Error::<Runtime>::DryRunInfeasible,
+			);
+
+			// an under-declared witness is rejected.
+			let mut small_witness = witness();
+			small_witness.voters -= 1;
+			assert_noop!(
+				MultiPhase::feasibility_check_dry_run(
+					Origin::signed(99),
+					solution.clone(),
+					small_witness,
+				),
+				Error::<Runtime>::DryRunWrongWitness,
+			);
+
+			// not in the unsigned phase.
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_noop!(
+				MultiPhase::feasibility_check_dry_run(Origin::signed(99), solution, witness()),
+				Error::<Runtime>::DryRunWrongPhase,
+			);
+		})
+	}
+
+	#[test]
+	fn queued_solution_of_another_round_is_not_used() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			assert_eq!(MultiPhase::queued_solution().unwrap().round, 1);
+
+			// the round changes under the feet of the queued solution.
+			<Round<Runtime>>::put(2);
+
+			assert_eq!(MultiPhase::elect_with_compute().unwrap().1, ElectionCompute::OnChain);
+			assert!(MultiPhase::queued_solution().is_none());
+		})
+	}
+
+	#[test]
+	fn score_priority_decides_improvement() {
+		let queued = [10, 100, 1000];
+		// less stake in total, but more balanced.
+		let candidate = [10, 90, 500];
+
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (mut solution, _) = MultiPhase::mine_solution(2, true).unwrap();
+			solution.score = candidate;
+			<QueuedSolutionScore<Runtime>>::put(queued);
+
+			assert_noop!(
+				MultiPhase::unsigned_pre_dispatch_checks(&solution),
+				Error::<Runtime>::PreDispatchWeakSubmission,
+			);
+		});
+
+		ExtBuilder::default().balance_first_scores().build_and_execute(|| {
+			roll_to(25);
+			let (mut solution, _) = MultiPhase::mine_solution(2, true).unwrap();
+			solution.score = candidate;
+			<QueuedSolutionScore<Runtime>>::put(queued);
+
+			assert_ok!(MultiPhase::unsigned_pre_dispatch_checks(&solution));
+		});
+	}
+
+	#[test]
+	fn balance_first_works() {
+		// variance dominates.
+		assert!(BalanceFirst::is_better([1, 1, 5], [10, 100, 10], Perbill::zero()));
+		assert!(!BalanceFirst::is_better([10, 100, 10], [1, 1, 5], Perbill::zero()));
+
+		// then the minimal stake, then the sum.
+		assert!(BalanceFirst::is_better([2, 10, 5], [1, 100, 5], Perbill::zero()));
+		assert!(BalanceFirst::is_better([1, 11, 5], [1, 10, 5], Perbill::zero()));
+		assert!(!BalanceFirst::is_better([1, 10, 5], [1, 10, 5], Perbill::zero()));
+
+		// the threshold applies to the variance as well.
+		assert!(!BalanceFirst::is_better([1, 11, 95], [1, 10, 100], Perbill::from_percent(10)));
+		assert!(BalanceFirst::is_better([1, 11, 85], [1, 10, 100], Perbill::from_percent(10)));
+	}
+
+	#[test]
+	fn score_compact_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (solution, _) = MultiPhase::mine_solution(2, true).unwrap();
+			let RoundSnapshot { voters, targets } = MultiPhase::snapshot().unwrap();
+
+			// no storage is needed.
+			MultiPhase::kill_snapshot();
+			assert_eq!(
+				MultiPhase::score_compact(&solution.compact, &voters, &targets),
+				Ok(solution.score),
+			);
+
+			// the targets must match the compact.
+			assert_eq!(
+				MultiPhase::score_compact(&solution.compact, &voters, &vec![]),
+				Err(sp_npos_elections::Error::CompactInvalidIndex),
+			);
+		})
+	}
+
+	#[test]
+	fn compute_history_works() {
+		ExtBuilder::default().compute_history_depth(2).build_and_execute(|| {
+			// round 1 falls back to on-chain.
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::compute_history(1), Some(ElectionCompute::OnChain));
+
+			// round 2 is unsigned.
+			roll_to(55);
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			roll_to(60);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::compute_history(1), Some(ElectionCompute::OnChain));
+			assert_eq!(MultiPhase::compute_history(2), Some(ElectionCompute::Unsigned));
+
+			// round 3 evicts round 1.
+			roll_to(90);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::compute_history(1), None);
+			assert_eq!(MultiPhase::compute_history(2), Some(ElectionCompute::Unsigned));
+			assert_eq!(MultiPhase::compute_history(3), Some(ElectionCompute::OnChain));
+		});
+
+		ExtBuilder::default().compute_history_depth(0).build_and_execute(|| {
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::compute_history(1), None);
+		})
+	}
+
+	#[test]
+	fn elect_with_compute_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			let expected = MultiPhase::queued_solution().unwrap().supports;
+
+			assert_eq!(
+				MultiPhase::elect_with_compute().unwrap(),
+				(expected, ElectionCompute::Unsigned),
+			);
+			assert_eq!(MultiPhase::round(), 2);
+			assert!(MultiPhase::current_phase().is_off());
+		});
+
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+
+			// nothing queued, the fallback is used.
+			let (_, compute) = MultiPhase::elect_with_compute().unwrap();
+			assert_eq!(compute, ElectionCompute::OnChain);
+		})
+	}
+
+	#[test]
+	fn winner_count_is_recorded() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_winner_count(), 2);
+			assert_eq!(
+				multi_phase_events().last().unwrap(),
+				&Event::ElectionFinalized(Some(ElectionCompute::OnChain), 2),
+			);
+		});
+
+		// the fallback can elect no more than the 4 targets.
+		ExtBuilder::default().desired_targets(6).build_and_execute(|| {
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_winner_count(), 4);
+		});
+
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			<LastWinnerCount<Runtime>>::put(2);
+			roll_to(25);
+			assert!(MultiPhase::elect().is_err());
+			assert_eq!(MultiPhase::last_winner_count(), 0);
+			assert_eq!(multi_phase_events().last().unwrap(), &Event::ElectionFinalized(None, 0));
+		});
+	}
+
+	#[test]
+	fn estimate_cost_works() {
+		ExtBuilder::default().fallabck(FallbackStrategy::OnChain).build_and_execute(|| {
+			// the cost of the on-chain fallback is unknown.
+			assert_eq!(MultiPhase::estimate_cost(8, 4), Weight::max_value());
+		});
+
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			// only the bookkeeping, which is free in the mock.
+			assert_eq!(MultiPhase::estimate_cost(8, 4), 0);
+		})
+	}
+
 	#[test]
 	fn number_of_voters_allowed_2sec_block() {
 		// Just a rough estimate with the substrate weights.
@@ -192,7 +192,14 @@ parameter_types! {
 		(40, 40, vec![40]),
 	];
 
+	pub static NonViableTargets: Vec<AccountId> = vec![];
+	pub static StalePrediction: bool = false;
+	pub static PredictionJitter: u64 = 0;
+	pub static Convictions: Vec<(AccountId, VoteWeight)> = vec![];
+
 	pub static Fallback: FallbackStrategy = FallbackStrategy::OnChain;
+	pub static UnsignedPhaseMode: multi_phase::UnsignedPhaseMode =
+		multi_phase::UnsignedPhaseMode::AlwaysActive;
 	pub static DesiredTargets: u32 = 2;
 	pub static SignedPhase: u64 = 10;
 	pub static UnsignedPhase: u64 = 5;
@@ -201,7 +208,23 @@ parameter_types! {
 	pub static MinerMaxIterations: u32 = 5;
 	pub static MinerTxPriority: u64 = 100;
 	pub static SolutionImprovementThreshold: Perbill = Perbill::zero();
+	pub static MinUnsignedInterval: u64 = 0;
+	pub static ElectionGap: u64 = 0;
 	pub static MinerMaxWeight: Weight = BlockWeights::get().max_block;
+	pub static MinSelfStake: VoteWeight = 0;
+	pub static MinBackersPerWinner: u32 = 0;
+	pub static MinWinnerSupport: ExtendedBalance = 0;
+	pub static RejectUnchangedSolutions: bool = false;
+	pub static SnapshotPageSize: u32 = 3;
+	pub static SnapshotDelay: u64 = 0;
+	pub static SnapshotClearPagesPerBlock: u32 = 0;
+	pub static ComputeHistoryDepth: u32 = 2;
+	pub static MaxSnapshotVoters: u32 = 0;
+	pub static MaxSnapshotTargets: u32 = 0;
+	pub static MinerCandidates: u32 = 1;
+	pub static MinerTimeBudget: u64 = 0;
+	pub static BalanceFirstScores: bool = false;
+	pub static PhaseTransitions: Vec<&'static str> = vec![];
 	pub static MockWeightInfo: bool = false;
 
 
@@ -239,6 +262,13 @@ impl multi_phase::weights::WeightInfo for DualMockWeightInfo {
 			<() as multi_phase::weights::WeightInfo>::on_initialize_open_unsigned_without_snapshot()
 		}
 	}
+	fn on_initialize_snapshot_page(p: u32) -> Weight {
+		if MockWeightInfo::get() {
+			Zero::zero()
+		} else {
+			<() as multi_phase::weights::WeightInfo>::on_initialize_snapshot_page(p)
+		}
+	}
 	fn submit_unsigned(v: u32, t: u32, a: u32, d: u32) -> Weight {
 		if MockWeightInfo::get() {
 			// 10 base
@@ -259,20 +289,85 @@ impl multi_phase::weights::WeightInfo for DualMockWeightInfo {
 	}
 }
 
+/// A miner objective that only cares about the minimum support of the solution.
+pub struct MinSupportObjective;
+impl multi_phase::unsigned::MinerObjective<AccountId> for MinSupportObjective {
+	fn candidates() -> u32 {
+		MinerCandidates::get()
+	}
+	fn is_better(this: &Supports<AccountId>, that: &Supports<AccountId>) -> bool {
+		this.evaluate()[0] > that.evaluate()[0]
+	}
+}
+
+/// Compares scores with [`multi_phase::BalanceFirst`] if [`BalanceFirstScores`] is set, and with
+/// the standard comparison otherwise.
+pub struct MockScorePriority;
+impl multi_phase::ScorePriority for MockScorePriority {
+	fn is_better(this: ElectionScore, that: ElectionScore, threshold: Perbill) -> bool {
+		if BalanceFirstScores::get() {
+			<multi_phase::BalanceFirst as multi_phase::ScorePriority>::is_better(
+				this, that, threshold,
+			)
+		} else {
+			<() as multi_phase::ScorePriority>::is_better(this, that, threshold)
+		}
+	}
+}
+
+/// Records every phase transition in [`PhaseTransitions`].
+pub struct PhaseTransitionRecorder;
+impl PhaseTransitionRecorder {
+	fn record(transition: &'static str) {
+		let mut transitions = PhaseTransitions::get();
+		transitions.push(transition);
+		PhaseTransitions::set(transitions);
+	}
+}
+impl multi_phase::OnPhaseTransition for PhaseTransitionRecorder {
+	fn on_signed_open() {
+		Self::record("signed");
+	}
+	fn on_unsigned_open() {
+		Self::record("unsigned");
+	}
+	fn on_elect() {
+		Self::record("elect");
+	}
+}
+
 impl crate::Config for Runtime {
 	type Event = Event;
 	type Currency = Balances;
 	type SignedPhase = SignedPhase;
 	type UnsignedPhase = UnsignedPhase;
 	type SolutionImprovementThreshold = SolutionImprovementThreshold;
+	type MinUnsignedInterval = MinUnsignedInterval;
+	type ElectionGap = ElectionGap;
 	type MinerMaxIterations = MinerMaxIterations;
 	type MinerMaxWeight = MinerMaxWeight;
 	type MinerTxPriority = MinerTxPriority;
+	type MinerObjective = MinSupportObjective;
+	type MinerTimeBudget = MinerTimeBudget;
+	type ScorePriority = MockScorePriority;
+	type MinSelfStake = MinSelfStake;
+	type MinBackersPerWinner = MinBackersPerWinner;
+	type MinWinnerSupport = MinWinnerSupport;
+	type RejectUnchangedSolutions = RejectUnchangedSolutions;
+	type SnapshotPageSize = SnapshotPageSize;
+	type SnapshotDelay = SnapshotDelay;
+	type SnapshotClearPagesPerBlock = SnapshotClearPagesPerBlock;
+	type MaxSnapshotVoters = MaxSnapshotVoters;
+	type MaxSnapshotTargets = MaxSnapshotTargets;
+	type ComputeHistoryDepth = ComputeHistoryDepth;
 	type DataProvider = StakingMock;
 	type WeightInfo = DualMockWeightInfo;
+	type OnPhaseTransition = PhaseTransitionRecorder;
+	type EmergencyOrigin = frame_system::EnsureRoot<AccountId>;
 	type BenchmarkingConfig = ();
 	type OnChainAccuracy = Perbill;
 	type Fallback = Fallback;
+	type UnsignedPhaseMode = UnsignedPhaseMode;
 	type CompactSolution = TestCompact;
 }
 
@@ -301,7 +396,25 @@ impl ElectionDataProvider<AccountId, u64> for StakingMock {
 		DesiredTargets::get()
 	}
 	fn next_election_prediction(now: u64) -> u64 {
-		now + EpochLength::get() - now % EpochLength::get()
+		if StalePrediction::get() {
+			return now.saturating_sub(1);
+		}
+		now + EpochLength::get() - now % EpochLength::get() + PredictionJitter::get()
+	}
+	fn is_viable_candidate(who: &AccountId) -> bool {
+		!NonViableTargets::get().contains(who)
+	}
+	fn voter_weight_adjustment(who: &AccountId, raw: VoteWeight) -> VoteWeight {
+		Convictions::get()
+			.into_iter()
+			.find(|(v, _)| v == who)
+			.map_or(raw, |(_, multiplier)| raw.saturating_mul(multiplier))
+	}
+	fn maybe_max_voters() -> Option<u32> {
+		Some(Voters::get().len() as u32)
+	}
+	fn maybe_max_targets() -> Option<u32> {
+		Some(Targets::get().len() as u32)
 	}
 }
 
@@ -314,6 +427,14 @@ impl ExtBuilder {
 		<SolutionImprovementThreshold>::set(p);
 		self
 	}
+	pub fn min_unsigned_interval(self, interval: u64) -> Self {
+		<MinUnsignedInterval>::set(interval);
+		self
+	}
+	pub fn election_gap(self, gap: u64) -> Self {
+		<ElectionGap>::set(gap);
+		self
+	}
 	pub fn phases(self, signed: u64, unsigned: u64) -> Self {
 		<SignedPhase>::set(signed);
 		<UnsignedPhase>::set(unsigned);
@@ -323,6 +444,10 @@ impl ExtBuilder {
 		<Fallback>::set(fallback);
 		self
 	}
+	pub fn unsigned_phase_mode(self, mode: multi_phase::UnsignedPhaseMode) -> Self {
+		<UnsignedPhaseMode>::set(mode);
+		self
+	}
 	pub fn miner_weight(self, weight: Weight) -> Self {
 		<MinerMaxWeight>::set(weight);
 		self
@@ -331,6 +456,51 @@ impl ExtBuilder {
 		<MockWeightInfo>::set(mock);
 		self
 	}
+	pub fn min_self_stake(self, stake: VoteWeight) -> Self {
+		<MinSelfStake>::set(stake);
+		self
+	}
+	pub fn min_backers(self, backers: u32) -> Self {
+		<MinBackersPerWinner>::set(backers);
+		self
+	}
+	pub fn min_winner_support(self, support: ExtendedBalance) -> Self {
+		<MinWinnerSupport>::set(support);
+		self
+	}
+	pub fn reject_unchanged(self, reject: bool) -> Self {
+		<RejectUnchangedSolutions>::set(reject);
+		self
+	}
+	pub fn compute_history_depth(self, depth: u32) -> Self {
+		<ComputeHistoryDepth>::set(depth);
+		self
+	}
+	pub fn snapshot_page_size(self, size: u32) -> Self {
+		<SnapshotPageSize>::set(size);
+		self
+	}
+	pub fn snapshot_delay(self, delay: u64) -> Self {
+		<SnapshotDelay>::set(delay);
+		self
+	}
+	pub fn snapshot_clear_pages_per_block(self, pages: u32) -> Self {
+		<SnapshotClearPagesPerBlock>::set(pages);
+		self
+	}
+	pub fn miner_time_budget(self, budget: u64) -> Self {
+		<MinerTimeBudget>::set(budget);
+		self
+	}
+	pub fn balance_first_scores(self) -> Self {
+		<BalanceFirstScores>::set(true);
+		self
+	}
+	pub fn max_snapshot_size(self, voters: u32, targets: u32) -> Self {
+		<MaxSnapshotVoters>::set(voters);
+		<MaxSnapshotTargets>::set(targets);
+		self
+	}
 	pub fn desired_targets(self, t: u32) -> Self {
 		<DesiredTargets>::set(t);
 		self
@@ -21,11 +21,14 @@ use crate::*;
 use frame_support::dispatch::DispatchResult;
 use frame_system::offchain::SubmitTransaction;
 use sp_npos_elections::{
-	seq_phragmen, CompactSolution, ElectionResult, assignment_ratio_to_staked_normalized,
-	assignment_staked_to_ratio_normalized,
+	seq_phragmen, Assignment, CompactSolution, ElectionResult, IdentifierT, StakedAssignment,
+	assignment_ratio_to_staked_normalized, assignment_staked_to_ratio_normalized,
 };
-use sp_runtime::{offchain::storage::StorageValueRef, traits::TrailingZeroInput};
-use sp_std::cmp::Ordering;
+use sp_runtime::{
+	offchain::{storage::StorageValueRef, Duration, Timestamp},
+	traits::{TrailingZeroInput, UniqueSaturatedInto},
+};
+use sp_std::{cmp::Ordering, collections::btree_map::BTreeMap, convert::TryInto};
 
 /// Storage key used to store the persistent offchain worker status.
 pub(crate) const OFFCHAIN_HEAD_DB: &[u8] = b"parity/multi-phase-unsigned-election";
@@ -34,18 +37,58 @@ pub(crate) const OFFCHAIN_HEAD_DB: &[u8] = b"parity/multi-phase-unsigned-electio
 /// within a window of 5 blocks.
 pub(crate) const OFFCHAIN_REPEAT: u32 = 5;
 
+/// Storage key used to cache the last solution mined by the offchain worker.
+pub(crate) const OFFCHAIN_CACHED_SOLUTION: &[u8] = b"parity/multi-phase-unsigned-election/solution";
+
+/// Storage key used to track the last unsigned transaction submitted by the offchain worker.
+pub(crate) const OFFCHAIN_LAST_SUBMISSION: &[u8] =
+	b"parity/multi-phase-unsigned-election/last-submission";
+
+/// Storage key of the [`MinerMetrics`] of this node, in the persistent offchain storage.
+///
+/// This is meant to be read by external tooling, e.g. via the `offchain_localStorageGet` RPC.
+pub const OFFCHAIN_MINER_METRICS: &[u8] = b"parity/multi-phase-unsigned-election/metrics";
+
+/// Counters of the outcomes of the offchain worker's mining, since the node's offchain storage was
+/// created.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+pub struct MinerMetrics {
+	/// The number of times mining was attempted.
+	pub attempts: u32,
+	/// The number of solutions submitted to the pool.
+	pub successes: u32,
+	/// The number of attempts that yielded no improvement over the queued solution.
+	pub not_worth_mining: u32,
+	/// The number of attempts that ran out of [`Config::MinerTimeBudget`].
+	pub timeouts: u32,
+	/// The number of solutions that could not be submitted to the pool.
+	pub pool_failures: u32,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum MinerError {
 	/// An internal error in the NPoS elections crate.
 	NposElections(sp_npos_elections::Error),
 	/// Snapshot data was unavailable unexpectedly.
 	SnapshotUnAvailable,
+	/// The desired number of targets is zero, there is nothing to mine.
+	ZeroDesiredTargets,
+	/// The mined solution is not better than the queued one, by at least
+	/// [`Config::SolutionImprovementThreshold`].
+	NotWorthMining,
 	/// Submitting a transaction to the pool failed.
 	PoolSubmissionFailed,
 	/// The pre-dispatch checks failed for the mined solution.
 	PreDispatchChecksFailed,
 	/// The solution generated from the miner is not feasible.
 	Feasibility(FeasibilityError),
+	/// [`Config::MinerTimeBudget`] was exceeded before any solution was mined.
+	Timeout,
+	/// A previously submitted transaction is still pending in the pool.
+	PendingSubmission,
+	/// The solution could not be trimmed to fit [`Config::MinerMaxWeight`] without dropping one of
+	/// its winners.
+	TrimmingFailed,
 }
 
 impl From<sp_npos_elections::Error> for MinerError {
@@ -60,16 +103,84 @@ impl From<FeasibilityError> for MinerError {
 	}
 }
 
+/// The objective that the miner of the unsigned phase optimizes for.
+///
+/// The miner computes [`MinerObjective::candidates`] solutions, each with a different number of
+/// balancing iterations, and keeps the best one according to [`MinerObjective::is_better`].
+pub trait MinerObjective<AccountId> {
+	/// The number of candidate solutions to compute and rank. Values lower than one are treated as
+	/// one.
+	fn candidates() -> u32 {
+		1
+	}
+
+	/// Returns true if the solution with `this` supports is better than the one with `that`.
+	fn is_better(this: &Supports<AccountId>, that: &Supports<AccountId>) -> bool;
+}
+
+/// The standard npos objective, as defined by [`sp_npos_elections::is_score_better`].
+impl<AccountId: IdentifierT> MinerObjective<AccountId> for () {
+	fn is_better(this: &Supports<AccountId>, that: &Supports<AccountId>) -> bool {
+		is_score_better::<Perbill>(this.evaluate(), that.evaluate(), Perbill::zero())
+	}
+}
+
 impl<T: Config> Pallet<T> {
 	/// Mine a new solution, and submit it back to the chain as an unsigned transaction.
+	///
+	/// If a solution mined earlier in this round is cached in the offchain storage and still
+	/// passes all the checks, it is re-used instead of mining a new one.
+	///
+	/// Nothing is submitted while the transaction of a previous call might still be pending, see
+	/// [`Pallet::ensure_no_pending_submission`].
 	pub fn mine_check_and_submit() -> Result<(), MinerError> {
-		let iters = Self::get_balancing_iters();
-		// get the solution, with a load of checks to ensure if submitted, IT IS ABSOLUTELY VALID.
-		let (raw_solution, witness) = Self::mine_and_check(iters)?;
+		Self::ensure_no_pending_submission()?;
+
+		let (raw_solution, witness) = match Self::restore_solution() {
+			Some(cached) => cached,
+			None => {
+				let iters = Self::get_balancing_iters();
+				// get the solution, with a load of checks to ensure if submitted, IT IS ABSOLUTELY
+				// VALID.
+				let mined = Self::mine_and_check(iters)?;
+				Self::save_solution(&mined);
+				mined
+			}
+		};
 
+		let score = raw_solution.score;
 		let call = Call::submit_unsigned(raw_solution, witness).into();
 		SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call)
-			.map_err(|_| MinerError::PoolSubmissionFailed)
+			.map_err(|_| MinerError::PoolSubmissionFailed)?;
+
+		StorageValueRef::persistent(&OFFCHAIN_LAST_SUBMISSION).set(&(
+			Self::round(),
+			<frame_system::Pallet<T>>::block_number(),
+			score,
+		));
+		Ok(())
+	}
+
+	/// Ensure that the unsigned transaction last submitted by this node is no longer pending.
+	///
+	/// A submission is considered pending until either its score is queued, the round changes, or
+	/// its longevity (the length of [`Config::UnsignedPhase`]) elapses.
+	pub(crate) fn ensure_no_pending_submission() -> Result<(), MinerError> {
+		let last = StorageValueRef::persistent(&OFFCHAIN_LAST_SUBMISSION)
+			.get::<(u32, T::BlockNumber, ElectionScore)>()
+			.flatten();
+
+		match last {
+			Some((round, submitted_at, score))
+				if round == Self::round()
+					&& Self::queued_solution_score() != Some(score)
+					&& <frame_system::Pallet<T>>::block_number()
+						< submitted_at.saturating_add(T::UnsignedPhase::get()) =>
+			{
+				Err(MinerError::PendingSubmission)
+			}
+			_ => Ok(()),
+		}
 	}
 
 	/// Mine a new npos solution, with all the relevant checks to make sure that it will be accepted
@@ -78,13 +189,50 @@ impl<T: Config> Pallet<T> {
 	/// If you want an unchecked solution, use [`Pallet::mine_solution`].
 	/// If you want a checked solution and submit it at the same time, use
 	/// [`Pallet::mine_check_and_submit`].
+	///
+	/// Returns [`MinerError::NotWorthMining`] if the mined solution does not improve the queued
+	/// one.
+	///
+	/// If the election algorithm fails or yields an empty solution, [`Pallet::trivial_solution`] is
+	/// used instead.
+	///
+	/// Mining respects [`Config::MinerTimeBudget`], thus this must be called from within an
+	/// offchain context if a budget is set.
 	pub fn mine_and_check(
 		iters: usize,
 	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
-		let (raw_solution, witness) = Self::mine_solution(iters)?;
+		let deadline = match T::MinerTimeBudget::get() {
+			0 => None,
+			budget => Some(sp_io::offchain::timestamp().add(Duration::from_millis(budget))),
+		};
+		let (raw_solution, witness) = match Self::mine_solution_until(iters, true, deadline) {
+			Ok((raw_solution, witness)) if raw_solution.compact.voter_count() > 0 => {
+				(raw_solution, witness)
+			}
+			Ok(_) | Err(MinerError::NposElections(_)) => {
+				log!(warn, "mining failed or yielded nothing, using the trivial solution.");
+				Self::trivial_solution()?
+			}
+			Err(why) => return Err(why),
+		};
+
+		// no need to go any further if this is not going to be an improvement.
+		let improves = Self::queued_solution_score().map_or(true, |queued| {
+			T::ScorePriority::is_better(
+				raw_solution.score,
+				queued,
+				T::SolutionImprovementThreshold::get(),
+			)
+		});
+		ensure!(improves, MinerError::NotWorthMining);
+		Self::miner_checks(&raw_solution)?;
+		Ok((raw_solution, witness))
+	}
 
+	/// Ensure that a mined solution passes the pre-dispatch checks and is feasible.
+	fn miner_checks(raw_solution: &RawSolution<CompactOf<T>>) -> Result<(), MinerError> {
 		// ensure that this will pass the pre-dispatch checks
-		Self::unsigned_pre_dispatch_checks(&raw_solution).map_err(|e| {
+		Self::unsigned_pre_dispatch_checks(raw_solution).map_err(|e| {
 			log!(warn, "pre-dispatch-checks failed for mined solution: {:?}", e);
 			MinerError::PreDispatchChecksFailed
 		})?;
@@ -97,56 +245,227 @@ impl<T: Config> Pallet<T> {
 			},
 		)?;
 
-		Ok((raw_solution, witness))
+		Ok(())
+	}
+
+	/// Update the [`MinerMetrics`] in the offchain storage with the outcome of a mining attempt.
+	pub(crate) fn record_miner_outcome(outcome: &Result<(), MinerError>) {
+		let storage = StorageValueRef::persistent(&OFFCHAIN_MINER_METRICS);
+		let mut metrics = storage.get::<MinerMetrics>().flatten().unwrap_or_default();
+
+		let bump = |counter: &mut u32| *counter = counter.saturating_add(1);
+		bump(&mut metrics.attempts);
+		match outcome {
+			Ok(_) => bump(&mut metrics.successes),
+			Err(MinerError::NotWorthMining) => bump(&mut metrics.not_worth_mining),
+			Err(MinerError::Timeout) => bump(&mut metrics.timeouts),
+			Err(MinerError::PoolSubmissionFailed) => bump(&mut metrics.pool_failures),
+			Err(_) => {}
+		}
+		storage.set(&metrics);
+	}
+
+	/// Cache a mined solution in the offchain storage.
+	pub(crate) fn save_solution(solution: &(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize)) {
+		StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION).set(solution);
+	}
+
+	/// Restore the solution cached in the offchain storage, if any.
+	///
+	/// The snapshot is only ever created once per round, thus a cached solution is only re-used if
+	/// it belongs to the current round and still passes all the checks of [`Self::miner_checks`]
+	/// against the current state.
+	pub(crate) fn restore_solution() -> Option<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize)>
+	{
+		let (raw_solution, witness) = StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION)
+			.get::<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize)>()
+			.flatten()?;
+
+		if raw_solution.round != Self::round() {
+			log!(debug, "cached solution is from round {}, ignoring.", raw_solution.round);
+			return None;
+		}
+
+		Self::miner_checks(&raw_solution).ok().map(|_| (raw_solution, witness))
 	}
 
 	/// Mine a new npos solution.
+	///
+	/// If [`Config::MinerObjective`] asks for more than one candidate, the `i`th candidate is
+	/// computed with `iters + i` balancing iterations, and the best one is kept.
+	///
+	/// The solution is reduced only if `reduce` is set, see [`Pallet::prepare_election_result`].
+	///
+	/// The inputs are sorted with [`sp_election_providers::sort_election_inputs`] before mining,
+	/// thus the winners do not depend on the order of the snapshot.
 	pub fn mine_solution(
 		iters: usize,
+		reduce: bool,
+	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
+		Self::mine_solution_until(iters, reduce, None)
+	}
+
+	/// Same as [`Pallet::mine_solution`], but stops computing candidates once the offchain
+	/// timestamp is past `deadline`, if any.
+	///
+	/// The election algorithm cannot be interrupted, thus the deadline is checked before and after
+	/// each run of it. A candidate that completes past the deadline is still considered, but no
+	/// further one is started, and the best candidate so far is used. [`MinerError::Timeout`] is
+	/// only returned if the deadline is past before the first candidate is started.
+	pub fn mine_solution_until(
+		iters: usize,
+		reduce: bool,
+		deadline: Option<Timestamp>,
+	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
+		Self::mine_solution_with(iters, reduce, || {
+			deadline.map_or(false, |deadline| sp_io::offchain::timestamp() > deadline)
+		})
+	}
+
+	/// Same as [`Pallet::mine_solution_until`], with `timed_out` telling if the deadline is past.
+	fn mine_solution_with(
+		iters: usize,
+		reduce: bool,
+		timed_out: impl Fn() -> bool,
+	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
+		// the election needs all the voters, with their votes.
+		let mut targets = Self::snapshot_targets().ok_or(MinerError::SnapshotUnAvailable)?;
+		let mut voters = Self::snapshot_voter_pages().flatten().collect::<Vec<_>>();
+		let desired_targets = Self::desired_targets().ok_or(MinerError::SnapshotUnAvailable)?;
+		ensure!(desired_targets > 0, MinerError::ZeroDesiredTargets);
+
+		// mine in a canonical order, such that all miners agree on the winners.
+		sp_election_providers::sort_election_inputs(&mut targets, &mut voters);
+
+		let mine = |iterations: usize| {
+			seq_phragmen::<_, CompactAccuracyOf<T>>(
+				desired_targets as usize,
+				targets.clone(),
+				voters.clone(),
+				Some((iterations, 0)),
+			)
+			.map_err::<MinerError, _>(Into::into)
+		};
+
+		ensure!(!timed_out(), MinerError::Timeout);
+		let mut best = mine(iters)?;
+		let candidates = T::MinerObjective::candidates();
+		if candidates > 1 {
+			let cache = helpers::generate_voter_cache::<T>(&voters);
+			let stake_of = helpers::stake_of_fn::<T>(&voters, &cache);
+			let supports_of = |result: &ElectionResult<T::AccountId, CompactAccuracyOf<T>>| {
+				let staked =
+					assignment_ratio_to_staked_normalized(result.assignments.clone(), &stake_of)?;
+				let winners = sp_npos_elections::to_without_backing(result.winners.clone());
+				sp_npos_elections::to_supports(&winners, &staked).map_err::<MinerError, _>(Into::into)
+			};
+
+			let mut best_supports = supports_of(&best)?;
+			for i in 1..candidates {
+				// checked after the previous candidate completed, and before starting this one.
+				if timed_out() {
+					log!(warn, "miner time budget exceeded, keeping the best of {} candidates", i);
+					break;
+				}
+				let candidate = mine(iters.saturating_add(i as usize))?;
+				let candidate_supports = supports_of(&candidate)?;
+				if T::MinerObjective::is_better(&candidate_supports, &best_supports) {
+					best = candidate;
+					best_supports = candidate_supports;
+				}
+			}
+		}
+
+		Self::prepare_election_result(best, reduce)
+	}
+
+	/// Create the trivial solution, in which the targets with the highest self-vote are elected,
+	/// each backed only by itself.
+	///
+	/// This is meant as a last resort, if mining fails. The solution is scored like any other, thus
+	/// it can be checked for feasibility, but it might elect less than [`DesiredTargets`] winners
+	/// if there are not enough self-voting targets.
+	pub fn trivial_solution(
 	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
-		let RoundSnapshot { voters, targets } =
-			Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
+		let targets = Self::snapshot_targets().ok_or(MinerError::SnapshotUnAvailable)?;
 		let desired_targets = Self::desired_targets().ok_or(MinerError::SnapshotUnAvailable)?;
 
-		seq_phragmen::<_, CompactAccuracyOf<T>>(
-			desired_targets as usize,
-			targets,
-			voters,
-			Some((iters, 0)),
-		)
-		.map_err(Into::into)
-		.and_then(Self::prepare_election_result)
+		// all the targets that vote for themselves, from the highest to the lowest self-stake.
+		let mut self_voters = Self::snapshot_voter_pages()
+			.flatten()
+			.filter(|(who, _, votes)| votes.contains(who) && targets.contains(who))
+			.map(|(who, stake, _)| (who, stake))
+			.collect::<Vec<_>>();
+		self_voters.sort_by(|(_, x), (_, y)| y.cmp(x));
+		self_voters.truncate(desired_targets as usize);
+
+		let winners = self_voters
+			.iter()
+			.map(|(who, stake)| (who.clone(), *stake as ExtendedBalance))
+			.collect::<Vec<_>>();
+		let assignments = self_voters
+			.into_iter()
+			.map(|(who, _)| Assignment {
+				who: who.clone(),
+				distribution: vec![(who, CompactAccuracyOf::<T>::one())],
+			})
+			.collect::<Vec<_>>();
+
+		// every voter has a single edge, there is nothing to reduce.
+		Self::prepare_election_result(ElectionResult { winners, assignments }, false)
 	}
 
 	/// Convert a raw solution from [`sp_npos_elections::ElectionResult`] to [`RawSolution`], which
 	/// is ready to be submitted to the chain.
 	///
-	/// Will always reduce the solution as well.
+	/// If `reduce` is set, [`sp_npos_elections::reduce`] is applied to the solution as well. This
+	/// removes redundant edges without altering the support of any winner, at a cost proportional
+	/// to the number of edges.
 	pub fn prepare_election_result(
 		election_result: ElectionResult<T::AccountId, CompactAccuracyOf<T>>,
+		reduce: bool,
 	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
 		// NOTE: This code path is generally not optimized as it is run offchain. Could use some at
 		// some point though.
 
-		// storage items. Note: we have already read this from storage, they must be in cache.
-		let RoundSnapshot { voters, targets } =
-			Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
+		// storage items. Note: we have already read this from storage, they must be in cache. Only
+		// the stake of each voter is needed, not their votes.
+		let targets = Self::snapshot_targets().ok_or(MinerError::SnapshotUnAvailable)?;
+		let voters = Self::snapshot_voter_pages()
+			.flat_map(|page| page.into_iter().map(|(who, stake, _)| (who, stake)))
+			.collect::<Vec<_>>();
 		let desired_targets = Self::desired_targets().ok_or(MinerError::SnapshotUnAvailable)?;
 
 		// closures.
-		let cache = helpers::generate_voter_cache::<T>(&voters);
+		let cache = voters
+			.iter()
+			.enumerate()
+			.map(|(i, (who, _))| (who.clone(), i))
+			.collect::<BTreeMap<_, _>>();
 		let voter_index = helpers::voter_index_fn::<T>(&cache);
 		let target_index = helpers::target_index_fn_linear::<T>(&targets);
-		let voter_at = helpers::voter_at_fn::<T>(&voters);
-		let target_at = helpers::target_at_fn::<T>(&targets);
-		let stake_of = helpers::stake_of_fn::<T>(&voters, &cache);
+		let stake_of = |who: &T::AccountId| -> VoteWeight {
+			cache.get(who).and_then(|i| voters.get(*i)).map_or(0, |(_, stake)| *stake)
+		};
 
-		let ElectionResult { assignments, winners } = election_result;
+		let ElectionResult { assignments, .. } = election_result;
 
 		// convert to staked and reduce.
 		let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)
 			.map_err::<MinerError, _>(Into::into)?;
-		sp_npos_elections::reduce(&mut staked);
+		if reduce {
+			let edge_count = |staked: &[StakedAssignment<T::AccountId>]| {
+				staked.iter().map(|a| a.distribution.len()).sum::<usize>()
+			};
+			let before = edge_count(&staked);
+			sp_npos_elections::reduce(&mut staked);
+			log!(
+				debug,
+				"miner: reduce removed {} of {} edges",
+				before.saturating_sub(edge_count(&staked)),
+				before,
+			);
+		}
 
 		// convert back to ration and make compact.
 		let ratio = assignment_staked_to_ratio_normalized(staked)?;
@@ -168,8 +487,17 @@ impl<T: Config> Pallet<T> {
 		let compact = Self::trim_compact(maximum_allowed_voters, compact, &voter_index)?;
 
 		// re-calc score.
-		let winners = sp_npos_elections::to_without_backing(winners);
-		let score = compact.clone().score(&winners, stake_of, voter_at, target_at)?;
+		let target_at = helpers::target_at_fn::<T>(&targets);
+		let voter_at = |i: CompactVoterIndexOf<T>| -> Option<T::AccountId> {
+			let i = <CompactVoterIndexOf<T> as TryInto<usize>>::try_into(i).ok()?;
+			voters.get(i).map(|(who, _)| who.clone())
+		};
+		let winners = compact
+			.unique_targets()
+			.into_iter()
+			.map(|i| target_at(i).ok_or(sp_npos_elections::Error::CompactInvalidIndex))
+			.collect::<Result<Vec<_>, _>>()?;
+		let score = compact.clone().score(&winners, &stake_of, voter_at, &target_at)?;
 
 		let round = Self::round();
 		Ok((RawSolution { compact, score, round }, size))
@@ -178,20 +506,23 @@ impl<T: Config> Pallet<T> {
 	/// Get a random number of iterations to run the balancing in the OCW.
 	///
 	/// Uses the offchain seed to generate a random number, maxed with
-	/// [`Config::MinerMaxIterations`].
+	/// [`Config::MinerMaxIterations`]. See [`Pallet::balancing_iters_from_seed`].
 	pub fn get_balancing_iters() -> usize {
 		match T::MinerMaxIterations::get() {
 			0 => 0,
-			max @ _ => {
-				let seed = sp_io::offchain::random_seed();
-				let random = <u32>::decode(&mut TrailingZeroInput::new(seed.as_ref()))
-					.expect("input is padded with zeroes; qed")
-					% max.saturating_add(1);
-				random as usize
-			}
+			max @ _ => Self::balancing_iters_from_seed(&sp_io::offchain::random_seed(), max),
 		}
 	}
 
+	/// Deterministically derive a number of balancing iterations in the range `[0, max]` from the
+	/// given `seed`.
+	pub fn balancing_iters_from_seed(seed: &[u8], max: u32) -> usize {
+		let random = <u32>::decode(&mut TrailingZeroInput::new(seed))
+			.expect("input is padded with zeroes; qed")
+			% max.saturating_add(1);
+		random as usize
+	}
+
 	/// Greedily reduce the size of the a solution to fit into the block, w.r.t. weight.
 	///
 	/// The weight of the solution is foremost a function of the number of voters (i.e.
@@ -206,6 +537,10 @@ impl<T: Config> Pallet<T> {
 	/// of the entire stake in the system. Nonetheless, some of the voters will be removed further
 	/// down the line.
 	///
+	/// Voters whose removal would drop one of the winners out of the solution (e.g. the sole backer
+	/// of a winner) are skipped, and the next least staked voter is considered instead. If not
+	/// enough voters can be removed, [`MinerError::TrimmingFailed`] is returned.
+	///
 	/// Indeed, the score must be computed **after** this step. If this step reduces the score too
 	/// much or remove a winner, then the solution must be discarded **after** this step.
 	pub fn trim_compact<FN>(
@@ -218,31 +553,57 @@ impl<T: Config> Pallet<T> {
 	{
 		match compact.voter_count().checked_sub(maximum_allowed_voters as usize) {
 			Some(to_remove) if to_remove > 0 => {
-				// grab all voters and sort them by least stake.
-				let RoundSnapshot { voters, .. } =
-					Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
-				let mut voters_sorted = voters
-					.into_iter()
-					.map(|(who, stake, _)| (who.clone(), stake))
+				// grab all voters, page by page, and sort them by least stake.
+				let mut voters_sorted = Self::snapshot_voter_pages()
+					.flat_map(|page| page.into_iter().map(|(who, stake, _)| (who, stake)))
 					.collect::<Vec<_>>();
 				voters_sorted.sort_by_key(|(_, y)| *y);
 
+				// the targets of each voter of the solution, and the number of backers of each
+				// target, both by index.
+				let index_of = |i: usize| i as u64;
+				let assignments = compact
+					.clone()
+					.into_assignment(
+						|v| Some(index_of(v.unique_saturated_into())),
+						|t| Some(index_of(t.unique_saturated_into())),
+					)
+					.map_err::<MinerError, _>(Into::into)?;
+				let mut backers: BTreeMap<u64, u32> = BTreeMap::new();
+				let mut targets_of: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+				for Assignment { who, distribution } in assignments {
+					let targets = distribution.into_iter().map(|(t, _)| t).collect::<Vec<_>>();
+					targets.iter().for_each(|t| *backers.entry(*t).or_default() += 1);
+					targets_of.insert(who, targets);
+				}
+
 				// start removing from the least stake. Iterate until we know enough have been
 				// removed.
 				let mut removed = 0;
-				for (maybe_index, _stake) in
-					voters_sorted.iter().map(|(who, stake)| (voter_index(&who), stake))
-				{
-					let index = maybe_index.ok_or(MinerError::SnapshotUnAvailable)?;
-					if compact.remove_voter(index) {
-						removed += 1
-					}
-
+				for (who, _stake) in voters_sorted.iter() {
 					if removed >= to_remove {
 						break;
 					}
+
+					let index = voter_index(who).ok_or(MinerError::SnapshotUnAvailable)?;
+					let targets = match targets_of.get(&index_of(index.unique_saturated_into())) {
+						Some(targets) => targets,
+						// not part of the solution.
+						None => continue,
+					};
+					if targets.iter().any(|t| backers.get(t).map_or(true, |b| *b <= 1)) {
+						// this voter is keeping a winner in the solution, skip it.
+						continue;
+					}
+					if compact.remove_voter(index) {
+						targets.iter().for_each(|t| {
+							backers.entry(*t).and_modify(|b| *b = b.saturating_sub(1));
+						});
+						removed += 1
+					}
 				}
 
+				ensure!(removed >= to_remove, MinerError::TrimmingFailed);
 				Ok(compact)
 			}
 			_ => {
@@ -373,9 +734,24 @@ impl<T: Config> Pallet<T> {
 	pub(crate) fn unsigned_pre_dispatch_checks(
 		solution: &RawSolution<CompactOf<T>>,
 	) -> DispatchResult {
+		// ensure the claimed score is not obviously malformed. This is the cheapest check.
+		ensure!(solution.validate_score_shape(), Error::<T>::PreDispatchMalformedScore);
+
 		// ensure solution is timely. Don't panic yet. This is a cheap check.
 		ensure!(Self::current_phase().is_unsigned_open(), Error::<T>::PreDispatchEarlySubmission);
 
+		// ensure solution is for this round, to prevent stale transactions from being re-used.
+		ensure!(Self::round() == solution.round, Error::<T>::PreDispatchWrongRound);
+
+		// ensure enough time has passed since the last accepted unsigned solution.
+		ensure!(
+			Self::last_unsigned_at().map_or(true, |last| {
+				<frame_system::Pallet<T>>::block_number()
+					>= last.saturating_add(T::MinUnsignedInterval::get())
+			}),
+			Error::<T>::PreDispatchTooSoon,
+		);
+
 		// ensure correct number of winners.
 		ensure!(
 			Self::desired_targets().unwrap_or_default()
@@ -385,9 +761,9 @@ impl<T: Config> Pallet<T> {
 
 		// ensure score is being improved. Panic henceforth.
 		ensure!(
-			Self::queued_solution().map_or(true, |q: ReadySolution<_>| is_score_better::<Perbill>(
+			Self::queued_solution_score().map_or(true, |queued| T::ScorePriority::is_better(
 				solution.score,
-				q.score,
+				queued,
 				T::SolutionImprovementThreshold::get()
 			)),
 			Error::<T>::PreDispatchWeakSubmission,
@@ -416,6 +792,9 @@ mod max_weight {
 		fn on_initialize_open_unsigned_without_snapshot() -> Weight {
 			unreachable!()
 		}
+		fn on_initialize_snapshot_page(_p: u32) -> Weight {
+			unreachable!()
+		}
 		fn submit_unsigned(v: u32, t: u32, a: u32, d: u32) -> Weight {
 			(0 * v + 0 * t + 1000 * a + 0 * d) as Weight
 		}
@@ -487,13 +866,13 @@ mod tests {
 	};
 	use frame_support::{dispatch::Dispatchable, traits::OffchainWorker};
 	use mock::Call as OuterCall;
-	use sp_election_providers::Assignment;
+	use sp_election_providers::{Assignment, ElectionProvider};
 	use sp_runtime::{traits::ValidateUnsigned, PerU16};
 
 	#[test]
 	fn validate_unsigned_retracts_wrong_phase() {
 		ExtBuilder::default().desired_targets(0).build_and_execute(|| {
-			let solution = RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
 			let call = Call::submit_unsigned(solution.clone(), witness());
 
 			// initial
@@ -553,7 +932,7 @@ mod tests {
 			roll_to(25);
 			assert!(MultiPhase::current_phase().is_unsigned());
 
-			let solution = RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
 			let call = Call::submit_unsigned(solution.clone(), witness());
 
 			// initial
@@ -566,6 +945,7 @@ mod tests {
 
 			// set a better score
 			let ready = ReadySolution { score: [10, 0, 0], ..Default::default() };
+			<QueuedSolutionScore<Runtime>>::put(ready.score);
 			<QueuedSolution<Runtime>>::put(ready);
 
 			// won't work anymore.
@@ -590,7 +970,7 @@ mod tests {
 			roll_to(25);
 			assert!(MultiPhase::current_phase().is_unsigned());
 
-			let solution = RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
 			let call = Call::submit_unsigned(solution.clone(), witness());
 			assert_eq!(solution.compact.unique_targets().len(), 0);
 
@@ -606,13 +986,115 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn validate_unsigned_retracts_wrong_round() {
+		ExtBuilder::default().desired_targets(0).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
+			assert_eq!(solution.round, MultiPhase::round());
+			let call = Call::submit_unsigned(solution.clone(), witness());
+			assert!(<MultiPhase as ValidateUnsigned>::validate_unsigned(
+				TransactionSource::Local,
+				&call
+			)
+			.is_ok());
+
+			// a solution from the previous round.
+			<Round<Runtime>>::put(2);
+			assert!(matches!(
+				<MultiPhase as ValidateUnsigned>::validate_unsigned(
+					TransactionSource::Local,
+					&call
+				)
+				.unwrap_err(),
+				TransactionValidityError::Invalid(InvalidTransaction::Custom(3))
+			));
+			assert!(matches!(
+				<MultiPhase as ValidateUnsigned>::pre_dispatch(&call).unwrap_err(),
+				TransactionValidityError::Invalid(InvalidTransaction::Custom(3))
+			));
+		})
+	}
+
+	#[test]
+	fn validate_unsigned_retracts_malformed_score() {
+		ExtBuilder::default().desired_targets(0).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// minimal support is more than the sum of supports.
+			let solution = RawSolution::<TestCompact> { score: [5, 4, 0], ..Default::default() };
+			assert!(!solution.validate_score_shape());
+			let call = Call::submit_unsigned(solution.clone(), witness());
+			assert!(matches!(
+				<MultiPhase as ValidateUnsigned>::validate_unsigned(
+					TransactionSource::Local,
+					&call
+				)
+				.unwrap_err(),
+				TransactionValidityError::Invalid(InvalidTransaction::Custom(7))
+			));
+			assert!(matches!(
+				<MultiPhase as ValidateUnsigned>::pre_dispatch(&call).unwrap_err(),
+				TransactionValidityError::Invalid(InvalidTransaction::Custom(7))
+			));
+
+			// sum of supports squared is more than the square of the sum of supports.
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 26], ..Default::default() };
+			assert!(!solution.validate_score_shape());
+			assert_noop!(
+				MultiPhase::unsigned_pre_dispatch_checks(&solution),
+				Error::<Runtime>::PreDispatchMalformedScore,
+			);
+
+			// the boundaries are fine.
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 25], ..Default::default() };
+			assert!(solution.validate_score_shape());
+			assert_ok!(MultiPhase::unsigned_pre_dispatch_checks(&solution));
+		})
+	}
+
+	#[test]
+	fn unsigned_submissions_are_rate_limited() {
+		ExtBuilder::default().min_unsigned_interval(3).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(MultiPhase::last_unsigned_at().is_none());
+
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
+			assert_ok!(MultiPhase::unsigned_pre_dispatch_checks(&solution));
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution.clone(), witness));
+			assert_eq!(MultiPhase::last_unsigned_at(), Some(25));
+
+			// too soon.
+			roll_to(27);
+			assert_noop!(
+				MultiPhase::unsigned_pre_dispatch_checks(&solution),
+				Error::<Runtime>::PreDispatchTooSoon,
+			);
+
+			// enough time has passed, now it is only not good enough.
+			roll_to(28);
+			assert_noop!(
+				MultiPhase::unsigned_pre_dispatch_checks(&solution),
+				Error::<Runtime>::PreDispatchWeakSubmission,
+			);
+
+			// cleared upon election.
+			let _ = MultiPhase::elect().unwrap();
+			assert!(MultiPhase::last_unsigned_at().is_none());
+		})
+	}
+
 	#[test]
 	fn priority_is_set() {
 		ExtBuilder::default().miner_tx_priority(20).desired_targets(0).build_and_execute(|| {
 			roll_to(25);
 			assert!(MultiPhase::current_phase().is_unsigned());
 
-			let solution = RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
 			let call = Call::submit_unsigned(solution.clone(), witness());
 
 			assert_eq!(
@@ -624,6 +1106,18 @@ mod tests {
 				.priority,
 				25
 			);
+
+			// two blocks into the unsigned phase.
+			roll_to(27);
+			assert_eq!(
+				<MultiPhase as ValidateUnsigned>::validate_unsigned(
+					TransactionSource::Local,
+					&call
+				)
+				.unwrap()
+				.priority,
+				27
+			);
 		})
 	}
 
@@ -638,7 +1132,7 @@ mod tests {
 			assert!(MultiPhase::current_phase().is_unsigned());
 
 			// This is in itself an invalid BS solution.
-			let solution = RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
 			let call = Call::submit_unsigned(solution.clone(), witness());
 			let outer_call: OuterCall = call.into();
 			let _ = outer_call.dispatch(Origin::none());
@@ -654,7 +1148,7 @@ mod tests {
 			assert!(MultiPhase::current_phase().is_unsigned());
 
 			// This solution is unfeasible as well, but we won't even get there.
-			let solution = RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+			let solution = RawSolution::<TestCompact> { score: [5, 5, 0], ..Default::default() };
 
 			let mut correct_witness = witness();
 			correct_witness.voters += 1;
@@ -676,12 +1170,35 @@ mod tests {
 			assert_eq!(MultiPhase::desired_targets().unwrap(), 2);
 
 			// mine seq_phragmen solution with 2 iters.
-			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
 
 			// ensure this solution is valid.
 			assert!(MultiPhase::queued_solution().is_none());
+			assert!(MultiPhase::queued_solution_score().is_none());
 			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
 			assert!(MultiPhase::queued_solution().is_some());
+			assert_eq!(
+				MultiPhase::queued_solution_score(),
+				Some(MultiPhase::queued_solution().unwrap().score),
+			);
+		})
+	}
+
+	#[test]
+	fn miner_objective_can_select_other_candidate() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// only one, unbalanced candidate.
+			let (default_solution, _) = MultiPhase::mine_solution(0, true).unwrap();
+
+			// the mock objective only cares about the minimum support, which balancing improves.
+			<MinerCandidates>::set(3);
+			let (solution, witness) = MultiPhase::mine_solution(0, true).unwrap();
+			assert!(solution.score[0] > default_solution.score[0]);
+
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
 		})
 	}
 
@@ -691,7 +1208,7 @@ mod tests {
 			roll_to(25);
 			assert!(MultiPhase::current_phase().is_unsigned());
 
-			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
 			let solution_weight = <Runtime as Config>::WeightInfo::submit_unsigned(
 				witness.voters,
 				witness.targets,
@@ -705,7 +1222,7 @@ mod tests {
 			// now reduce the max weight
 			<MinerMaxWeight>::set(25);
 
-			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			let (solution, witness) = MultiPhase::mine_solution(2, true).unwrap();
 			let solution_weight = <Runtime as Config>::WeightInfo::submit_unsigned(
 				witness.voters,
 				witness.targets,
@@ -718,13 +1235,161 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn trim_compact_keeps_all_winners() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let RoundSnapshot { voters, targets } = MultiPhase::snapshot().unwrap();
+			let cache = helpers::generate_voter_cache::<Runtime>(&voters);
+			let voter_index = helpers::voter_index_fn::<Runtime>(&cache);
+			let target_index = helpers::target_index_fn_linear::<Runtime>(&targets);
+
+			// voter 1 is among the least staked, and the sole backer of 10.
+			let assignments = vec![
+				Assignment { who: 1, distribution: vec![(10, PerU16::one())] },
+				Assignment { who: 2, distribution: vec![(40, PerU16::one())] },
+				Assignment { who: 3, distribution: vec![(40, PerU16::one())] },
+				Assignment { who: 40, distribution: vec![(40, PerU16::one())] },
+			];
+			let compact =
+				TestCompact::from_assignment(assignments, &voter_index, &target_index).unwrap();
+			assert_eq!(compact.voter_count(), 4);
+			assert_eq!(compact.unique_targets().len(), 2);
+
+			// naively removing the least staked voter would drop 10. Voter 2 is removed instead.
+			let trimmed = MultiPhase::trim_compact(3, compact.clone(), &voter_index).unwrap();
+			assert_eq!(trimmed.voter_count(), 3);
+			assert_eq!(trimmed.unique_targets().len(), 2);
+
+			// only voters 2 and 3 can be removed, leaving 1 and 40 as the sole backers of a winner.
+			assert_eq!(
+				MultiPhase::trim_compact(1, compact, &voter_index).unwrap_err(),
+				MinerError::TrimmingFailed,
+			);
+		})
+	}
+
+	#[test]
+	fn miner_does_not_bother_if_not_improving() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (solution, witness) = MultiPhase::mine_and_check(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+
+			// the same solution is mined again, which is not an improvement.
+			assert_eq!(MultiPhase::mine_and_check(2).unwrap_err(), MinerError::NotWorthMining);
+		})
+	}
+
+	#[test]
+	fn mine_solution_is_independent_of_snapshot_order() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// two equally backed targets, competing for a single seat.
+			let voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)> =
+				vec![(1, 10, vec![10]), (2, 10, vec![20]), (3, 10, vec![10, 20])];
+			let targets: Vec<AccountId> = vec![10, 20];
+			crate::DesiredTargets::<Runtime>::put(1);
+			<SnapshotMetadata<Runtime>>::put(SolutionOrSnapshotSize { voters: 3, targets: 2 });
+
+			let winners_of = |voters, targets| {
+				MultiPhase::store_snapshot(voters, targets);
+				let (solution, _) = MultiPhase::mine_solution(2, true).unwrap();
+				MultiPhase::feasibility_check(solution, ElectionCompute::Unsigned)
+					.unwrap()
+					.supports
+					.into_iter()
+					.map(|(who, _)| who)
+					.collect::<Vec<_>>()
+			};
+
+			let ordered = winners_of(voters.clone(), targets.clone());
+			let reversed =
+				winners_of(voters.into_iter().rev().collect(), targets.into_iter().rev().collect());
+			assert_eq!(ordered.len(), 1);
+			assert_eq!(ordered, reversed);
+		})
+	}
+
+	#[test]
+	fn mine_solution_can_skip_reduce() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (reduced, _) = MultiPhase::mine_solution(2, true).unwrap();
+			let (unreduced, _) = MultiPhase::mine_solution(2, false).unwrap();
+
+			// reducing never adds edges, and both are acceptable.
+			assert!(reduced.compact.edge_count() <= unreduced.compact.edge_count());
+			assert_ok!(MultiPhase::feasibility_check(reduced, ElectionCompute::Unsigned));
+			assert_ok!(MultiPhase::feasibility_check(unreduced, ElectionCompute::Unsigned));
+		})
+	}
+
+	#[test]
+	fn trivial_solution_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// 30 and 40 have the highest self-votes.
+			let (solution, witness) = MultiPhase::trivial_solution().unwrap();
+			assert_eq!(solution.compact.voter_count(), 2);
+			assert_eq!(solution.compact.unique_targets().len(), 2);
+			assert_eq!(solution.score, [30, 70, 30 * 30 + 40 * 40]);
+
+			// it is a valid solution.
+			let ready = MultiPhase::feasibility_check(solution.clone(), ElectionCompute::Unsigned)
+				.unwrap();
+			assert_eq!(ready.supports.iter().map(|(w, _)| *w).collect::<Vec<_>>(), vec![30, 40]);
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+		})
+	}
+
+	#[test]
+	fn balancing_iters_from_seed_works() {
+		// zero seed, zero iterations.
+		assert_eq!(MultiPhase::balancing_iters_from_seed(&[0u8; 32], 5), 0);
+		// empty seed is padded with zeros.
+		assert_eq!(MultiPhase::balancing_iters_from_seed(&[], 5), 0);
+		// the seed is interpreted as a little endian u32, and capped.
+		assert_eq!(MultiPhase::balancing_iters_from_seed(&[3, 0, 0, 0], 5), 3);
+		assert_eq!(MultiPhase::balancing_iters_from_seed(&[5, 0, 0, 0], 5), 5);
+		assert_eq!(MultiPhase::balancing_iters_from_seed(&[7, 0, 0, 0], 5), 1);
+		// zero max, always zero.
+		assert_eq!(MultiPhase::balancing_iters_from_seed(&[7, 0, 0, 0], 0), 0);
+	}
+
+	#[test]
+	fn miner_rejects_zero_desired_targets() {
+		ExtBuilder::default().desired_targets(0).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_eq!(
+				MultiPhase::mine_solution(2, true).unwrap_err(),
+				MinerError::ZeroDesiredTargets,
+			);
+		})
+	}
+
 	#[test]
 	fn miner_will_not_submit_if_not_enough_winners() {
-		let (mut ext, _) = ExtBuilder::default().desired_targets(8).build_offchainify(0);
+		let (mut ext, _) = ExtBuilder::default().build_offchainify(0);
 		ext.execute_with(|| {
 			roll_to(25);
 			assert!(MultiPhase::current_phase().is_unsigned());
 
+			// the snapshot would clamp this to the number of targets, so we force it.
+			crate::DesiredTargets::<Runtime>::put(8);
+
 			// mine seq_phragmen solution with 2 iters.
 			assert_eq!(
 				MultiPhase::mine_check_and_submit().unwrap_err(),
@@ -754,7 +1419,8 @@ mod tests {
 						distribution: vec![(10, PerU16::one())],
 					}],
 				};
-				let (solution, witness) = MultiPhase::prepare_election_result(result).unwrap();
+				let (solution, witness) =
+					MultiPhase::prepare_election_result(result, true).unwrap();
 				assert_ok!(MultiPhase::unsigned_pre_dispatch_checks(&solution));
 				assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
 				assert_eq!(MultiPhase::queued_solution().unwrap().score[0], 10);
@@ -771,7 +1437,7 @@ mod tests {
 						},
 					],
 				};
-				let (solution, _) = MultiPhase::prepare_election_result(result).unwrap();
+				let (solution, _) = MultiPhase::prepare_election_result(result, true).unwrap();
 				// 12 is not 50% more than 10
 				assert_eq!(solution.score[0], 12);
 				assert_noop!(
@@ -793,7 +1459,8 @@ mod tests {
 						},
 					],
 				};
-				let (solution, witness) = MultiPhase::prepare_election_result(result).unwrap();
+				let (solution, witness) =
+					MultiPhase::prepare_election_result(result, true).unwrap();
 				assert_eq!(solution.score[0], 17);
 
 				// and it is fine
@@ -856,6 +1523,166 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn miner_respects_time_budget() {
+		let (mut ext, _) = ExtBuilder::default().miner_time_budget(10).build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			sp_io::offchain::sleep_until(Timestamp::from_unix_millis(100));
+
+			// deadline already past.
+			let deadline = Some(Timestamp::from_unix_millis(50));
+			assert_eq!(
+				MultiPhase::mine_solution_until(2, true, deadline).unwrap_err(),
+				MinerError::Timeout,
+			);
+
+			// deadline not yet past.
+			let deadline = Some(Timestamp::from_unix_millis(100));
+			assert_eq!(
+				MultiPhase::mine_solution_until(2, true, deadline).unwrap(),
+				MultiPhase::mine_solution(2, true).unwrap(),
+			);
+
+			// the budget of the config starts counting upon mining, the clock does not move here.
+			assert_ok!(MultiPhase::mine_and_check(2));
+		})
+	}
+
+	#[test]
+	fn miner_time_budget_runs_out_while_mining() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// the deadline is past before any candidate is computed.
+			assert_eq!(
+				MultiPhase::mine_solution_with(2, true, || true).unwrap_err(),
+				MinerError::Timeout,
+			);
+
+			// `after` checks of the deadline pass, then it is past.
+			let timed_out_after = |after: usize| {
+				let checks = std::cell::Cell::new(0);
+				move || {
+					checks.set(checks.get() + 1);
+					checks.get() > after
+				}
+			};
+
+			// the deadline passes while computing the first of three candidates, which is kept.
+			<MinerCandidates>::set(3);
+			let solution = MultiPhase::mine_solution_with(2, true, timed_out_after(1)).unwrap();
+			<MinerCandidates>::set(1);
+			assert_eq!(solution, MultiPhase::mine_solution(2, true).unwrap());
+
+			// the deadline passes while computing the second of three candidates, which is still
+			// considered, but the third is never started.
+			<MinerCandidates>::set(3);
+			let solution = MultiPhase::mine_solution_with(2, true, timed_out_after(2)).unwrap();
+			<MinerCandidates>::set(2);
+			assert_eq!(solution, MultiPhase::mine_solution(2, true).unwrap());
+		})
+	}
+
+	#[test]
+	fn ocw_caches_solution() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(MultiPhase::restore_solution().is_none());
+
+			MultiPhase::offchain_worker(25);
+			assert_eq!(pool.read().transactions.len(), 1);
+
+			// the submitted solution is now cached.
+			let (solution, witness) = MultiPhase::restore_solution().unwrap();
+			let encoded = pool.read().transactions[0].clone();
+			let extrinsic: Extrinsic = Decode::decode(&mut &*encoded).unwrap();
+			assert_eq!(
+				extrinsic.call,
+				OuterCall::MultiPhase(Call::submit_unsigned(solution, witness)),
+			);
+
+			// a new round invalidates the cache.
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::restore_solution().is_none());
+		})
+	}
+
+	#[test]
+	fn ocw_does_not_resubmit_while_pending() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			assert_eq!(pool.read().transactions.len(), 1);
+
+			// not included yet.
+			assert_eq!(
+				MultiPhase::mine_check_and_submit().unwrap_err(),
+				MinerError::PendingSubmission,
+			);
+			assert_eq!(pool.read().transactions.len(), 1);
+
+			// the longevity of the transaction has elapsed.
+			System::set_block_number(25 + UnsignedPhase::get());
+			assert_ok!(MultiPhase::ensure_no_pending_submission());
+			System::set_block_number(26);
+			assert!(MultiPhase::ensure_no_pending_submission().is_err());
+
+			// once included, the miner is free to try again.
+			let (solution, witness) = MultiPhase::restore_solution().unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			assert_ok!(MultiPhase::ensure_no_pending_submission());
+			assert_eq!(
+				MultiPhase::mine_check_and_submit().unwrap_err(),
+				MinerError::NotWorthMining,
+			);
+		})
+	}
+
+	#[test]
+	fn ocw_records_metrics() {
+		let (mut ext, _) = ExtBuilder::default().build_offchainify(0);
+		ext.execute_with(|| {
+			let metrics = || {
+				StorageValueRef::persistent(&OFFCHAIN_MINER_METRICS)
+					.get::<MinerMetrics>()
+					.flatten()
+					.unwrap_or_default()
+			};
+			roll_to(25);
+			assert_eq!(metrics(), MinerMetrics::default());
+
+			MultiPhase::offchain_worker(25);
+			assert_eq!(metrics(), MinerMetrics { attempts: 1, successes: 1, ..Default::default() });
+
+			// the submission is pending, only the attempt is counted.
+			StorageValueRef::persistent(&OFFCHAIN_HEAD_DB).clear();
+			MultiPhase::offchain_worker(25);
+			assert_eq!(metrics(), MinerMetrics { attempts: 2, successes: 1, ..Default::default() });
+
+			MultiPhase::record_miner_outcome(&Err(MinerError::NotWorthMining));
+			MultiPhase::record_miner_outcome(&Err(MinerError::Timeout));
+			MultiPhase::record_miner_outcome(&Err(MinerError::PoolSubmissionFailed));
+			assert_eq!(
+				metrics(),
+				MinerMetrics {
+					attempts: 5,
+					successes: 1,
+					not_worth_mining: 1,
+					timeouts: 1,
+					pool_failures: 1,
+				},
+			);
+		})
+	}
+
 	#[test]
 	fn ocw_can_submit_to_pool() {
 		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
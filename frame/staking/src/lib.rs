@@ -3304,16 +3304,54 @@ impl<T: Config> Module<T> {
 	///
 	/// Note that this is VERY expensive. Use with care.
 	pub fn get_npos_voters() -> Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		Self::npos_voters(
+			<Validators<T>>::iter().map(|(v, _)| v),
+			<Nominators<T>>::iter(),
+			usize::max_value(),
+		)
+	}
+
+	/// Get at most `limit` of the voters of [`Self::get_npos_voters`], in the same order, starting
+	/// right after the voter `last`, or from the first voter if `None`.
+	///
+	/// Only the returned voters are read from storage.
+	pub fn get_npos_voters_after(
+		last: Option<&T::AccountId>,
+		limit: usize,
+	) -> Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		let (validators, nominators) = match last {
+			None => (Some(<Validators<T>>::iter()), <Nominators<T>>::iter()),
+			// all the validators come first, so they have all been read already.
+			Some(last) if <Nominators<T>>::contains_key(last) =>
+				(None, <Nominators<T>>::iter_from(<Nominators<T>>::hashed_key_for(last))),
+			Some(last) => (
+				Some(<Validators<T>>::iter_from(<Validators<T>>::hashed_key_for(last))),
+				<Nominators<T>>::iter(),
+			),
+		};
+
+		Self::npos_voters(
+			validators.into_iter().flatten().map(|(v, _)| v),
+			nominators,
+			limit,
+		)
+	}
+
+	/// Build at most `limit` voters out of the given validators, each with a self vote, followed
+	/// by the given nominators.
+	fn npos_voters(
+		validators: impl Iterator<Item = T::AccountId>,
+		nominators: impl Iterator<Item = (T::AccountId, Nominations<T::AccountId>)>,
+		limit: usize,
+	) -> Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
 		let weight_of = Self::slashable_balance_of_fn();
-		let mut all_voters = Vec::new();
 
-		for (validator, _) in <Validators<T>>::iter() {
+		let self_votes = validators.map(|validator| {
 			// append self vote
-			let self_vote = (validator.clone(), weight_of(&validator), vec![validator.clone()]);
-			all_voters.push(self_vote);
-		}
+			(validator.clone(), weight_of(&validator), vec![validator])
+		});
 
-		for (nominator, nominations) in <Nominators<T>>::iter() {
+		let nominations = nominators.map(|(nominator, nominations)| {
 			let Nominations { submitted_in, mut targets, suppressed: _ } = nominations;
 
 			// Filter out nomination targets which were nominated before the most recent
@@ -3324,10 +3362,10 @@ impl<T: Config> Module<T> {
 			});
 
 			let vote_weight = weight_of(&nominator);
-			all_voters.push((nominator, vote_weight, targets))
-		}
+			(nominator, vote_weight, targets)
+		});
 
-		all_voters
+		self_votes.chain(nominations).take(limit).collect()
 	}
 
 	pub fn get_npos_targets() -> Vec<T::AccountId> {
@@ -3346,6 +3384,13 @@ impl<T: Config> sp_election_providers::ElectionDataProvider<T::AccountId, T::Blo
 		Self::get_npos_voters()
 	}
 
+	fn voters_after(
+		last: Option<&T::AccountId>,
+		limit: u32,
+	) -> Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		Self::get_npos_voters_after(last, limit as usize)
+	}
+
 	fn targets() -> Vec<T::AccountId> {
 		Self::get_npos_targets()
 	}
@@ -5070,6 +5070,30 @@ mod election_data_provider {
 		})
 	}
 
+	#[test]
+	fn voters_after_pages_through_all_voters() {
+		ExtBuilder::default().build().execute_with(|| {
+			bond_nominator(1000, 1001, 100, vec![11]);
+			bond_nominator(2000, 2001, 100, vec![21, 31]);
+			let voters = <Staking as ElectionDataProvider<AccountId, BlockNumber>>::voters();
+			assert_eq!(voters.len(), 6);
+
+			for limit in 1..=voters.len() as u32 + 1 {
+				let mut paged: Vec<(AccountId, VoteWeight, Vec<AccountId>)> = vec![];
+				loop {
+					let last = paged.last().map(|(who, _, _)| *who);
+					let page = Staking::voters_after(last.as_ref(), limit);
+					let done = page.len() < limit as usize;
+					paged.extend(page);
+					if done {
+						break;
+					}
+				}
+				assert_eq!(paged, voters);
+			}
+		})
+	}
+
 	#[test]
 	fn estimate_next_election_works() {
 		ExtBuilder::default().session_per_era(5).period(5).build().execute_with(|| {
@@ -111,6 +111,26 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn map_iter_from_works() {
+		let t = GenesisConfig::default().build_storage().unwrap();
+		TestExternalities::new(t).execute_with(|| {
+			for i in 0u32..10u32 {
+				unhashed::put(&NumberMap::hashed_key_for(&i), &(i as u64));
+			}
+
+			// the starting key itself is excluded.
+			assert_eq!(
+				NumberMap::iter_from(NumberMap::hashed_key_for(&4u32)).collect::<Vec<_>>(),
+				(5..10).map(|x| (x as u32, x as u64)).collect::<Vec<_>>(),
+			);
+
+			// the starting key need not exist.
+			unhashed::kill(&NumberMap::hashed_key_for(&4u32));
+			assert_eq!(NumberMap::iter_from(NumberMap::hashed_key_for(&4u32)).count(), 5);
+		})
+	}
+
 	#[test]
 	fn try_mutate_works() {
 		let t = GenesisConfig::default().build_storage().unwrap();
@@ -0,0 +1,92 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of the on-chain election providers, comparing inputs that are declared presorted
+//! with [`onchain::Config::INPUTS_PRESORTED`] to inputs of any order.
+
+#![cfg(feature = "bench")]
+#![feature(test)]
+
+extern crate test;
+use test::Bencher;
+
+use sp_election_providers::{onchain, ElectionDataProvider, ElectionProvider, VoteWeight};
+use sp_runtime::Perbill;
+
+type AccountId = u64;
+type BlockNumber = u32;
+
+const TARGETS: u64 = 100;
+const VOTERS: u64 = 1_000;
+const EDGES: u64 = 16;
+const TO_ELECT: u32 = 10;
+
+/// Provides voters and targets that are already sorted by account id.
+struct DataProvider;
+impl ElectionDataProvider<AccountId, BlockNumber> for DataProvider {
+	fn targets() -> Vec<AccountId> {
+		(0..TARGETS).collect()
+	}
+	fn voters() -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+		(TARGETS..TARGETS + VOTERS)
+			.map(|v| (v, 1_000 + v, (0..EDGES).map(|e| (v + e * 7) % TARGETS).collect()))
+			.collect()
+	}
+	fn desired_targets() -> u32 {
+		TO_ELECT
+	}
+	fn next_election_prediction(now: BlockNumber) -> BlockNumber {
+		now
+	}
+}
+
+struct Presorted;
+impl onchain::Config for Presorted {
+	type AccountId = AccountId;
+	type BlockNumber = BlockNumber;
+	type Accuracy = Perbill;
+	type DataProvider = DataProvider;
+	const INPUTS_PRESORTED: bool = true;
+}
+
+struct AnyOrder;
+impl onchain::Config for AnyOrder {
+	type AccountId = AccountId;
+	type BlockNumber = BlockNumber;
+	type Accuracy = Perbill;
+	type DataProvider = DataProvider;
+}
+
+#[bench]
+fn seq_phragmen_presorted(b: &mut Bencher) {
+	b.iter(|| onchain::OnChainSequentialPhragmen::<Presorted>::elect().unwrap());
+}
+
+#[bench]
+fn seq_phragmen_any_order(b: &mut Bencher) {
+	b.iter(|| onchain::OnChainSequentialPhragmen::<AnyOrder>::elect().unwrap());
+}
+
+#[bench]
+fn phragmms_presorted(b: &mut Bencher) {
+	b.iter(|| onchain::OnChainPhragmms::<Presorted>::elect().unwrap());
+}
+
+#[bench]
+fn phragmms_any_order(b: &mut Bencher) {
+	b.iter(|| onchain::OnChainPhragmms::<AnyOrder>::elect().unwrap());
+}
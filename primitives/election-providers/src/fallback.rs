@@ -0,0 +1,169 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [`ElectionProvider`] that composes two others, falling back to the second one if the first
+//! one fails.
+
+use crate::{ElectionProvider, Supports, Weight};
+use sp_std::marker::PhantomData;
+
+/// An election provider that elects with `Primary`, and if that fails, with `Secondary`.
+///
+/// Both providers must share the same data provider. The error of `Primary` is discarded, only
+/// the error of `Secondary` is returned if both fail.
+///
+/// Note that [`ElectionProvider::ongoing`] of `Primary` is deliberately not consulted. A stateful
+/// provider is ongoing precisely while it prepares the result that [`ElectionProvider::elect`] is
+/// expected to return, e.g. during the unsigned phase of the multi-phase pallet, thus being ongoing
+/// does not indicate that no result exists. `Primary` signals that with an error instead.
+pub struct FallbackProvider<Primary, Secondary>(PhantomData<(Primary, Secondary)>);
+
+impl<AccountId, BlockNumber, Primary, Secondary> ElectionProvider<AccountId, BlockNumber>
+	for FallbackProvider<Primary, Secondary>
+where
+	Primary: ElectionProvider<AccountId, BlockNumber>,
+	Secondary: ElectionProvider<AccountId, BlockNumber, DataProvider = Primary::DataProvider>,
+{
+	type Error = Secondary::Error;
+	type DataProvider = Primary::DataProvider;
+
+	fn elect() -> Result<Supports<AccountId>, Self::Error> {
+		Primary::elect().or_else(|_| Secondary::elect())
+	}
+
+	/// The worst case, in which both providers are used.
+	fn estimate_cost(voters: u32, targets: u32) -> Weight {
+		Primary::estimate_cost(voters, targets)
+			.saturating_add(Secondary::estimate_cost(voters, targets))
+	}
+
+	fn ongoing() -> bool {
+		Primary::ongoing() || Secondary::ongoing()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Support;
+
+	type AccountId = u64;
+	type BlockNumber = u32;
+
+	/// Always elects 10.
+	struct Succeeds;
+	impl ElectionProvider<AccountId, BlockNumber> for Succeeds {
+		type Error = &'static str;
+		type DataProvider = ();
+
+		fn elect() -> Result<Supports<AccountId>, Self::Error> {
+			Ok(vec![(10, Support { total: 10, voters: vec![(10, 10)] })])
+		}
+
+		fn estimate_cost(_: u32, _: u32) -> Weight {
+			1
+		}
+	}
+
+	/// Always elects 20.
+	struct AlsoSucceeds;
+	impl ElectionProvider<AccountId, BlockNumber> for AlsoSucceeds {
+		type Error = &'static str;
+		type DataProvider = ();
+
+		fn elect() -> Result<Supports<AccountId>, Self::Error> {
+			Ok(vec![(20, Support { total: 20, voters: vec![(20, 20)] })])
+		}
+	}
+
+	/// Always elects 30, and is always ongoing.
+	struct OngoingSucceeds;
+	impl ElectionProvider<AccountId, BlockNumber> for OngoingSucceeds {
+		type Error = &'static str;
+		type DataProvider = ();
+
+		fn elect() -> Result<Supports<AccountId>, Self::Error> {
+			Ok(vec![(30, Support { total: 30, voters: vec![(30, 30)] })])
+		}
+
+		fn ongoing() -> bool {
+			true
+		}
+	}
+
+	/// Always fails, and is always ongoing.
+	struct Fails;
+	impl ElectionProvider<AccountId, BlockNumber> for Fails {
+		type Error = &'static str;
+		type DataProvider = ();
+
+		fn elect() -> Result<Supports<AccountId>, Self::Error> {
+			Err("fails")
+		}
+
+		fn estimate_cost(_: u32, _: u32) -> Weight {
+			2
+		}
+
+		fn ongoing() -> bool {
+			true
+		}
+	}
+
+	fn elect<P>() -> Result<Supports<AccountId>, P::Error>
+	where
+		P: ElectionProvider<AccountId, BlockNumber>,
+	{
+		P::elect()
+	}
+
+	fn estimate_cost<P: ElectionProvider<AccountId, BlockNumber>>() -> Weight {
+		P::estimate_cost(0, 0)
+	}
+
+	fn ongoing<P: ElectionProvider<AccountId, BlockNumber>>() -> bool {
+		P::ongoing()
+	}
+
+	#[test]
+	fn primary_is_used_if_it_succeeds() {
+		assert_eq!(elect::<FallbackProvider<Succeeds, AlsoSucceeds>>(), elect::<Succeeds>());
+		assert_eq!(elect::<FallbackProvider<Succeeds, Fails>>(), elect::<Succeeds>());
+	}
+
+	#[test]
+	fn primary_is_used_even_if_ongoing() {
+		assert_eq!(
+			elect::<FallbackProvider<OngoingSucceeds, Succeeds>>(),
+			elect::<OngoingSucceeds>(),
+		);
+	}
+
+	#[test]
+	fn secondary_is_used_if_primary_fails() {
+		assert_eq!(elect::<FallbackProvider<Fails, Succeeds>>(), elect::<Succeeds>());
+		assert_eq!(elect::<FallbackProvider<Fails, Fails>>(), Err("fails"));
+	}
+
+	#[test]
+	fn estimate_cost_and_ongoing_combine_both() {
+		assert_eq!(estimate_cost::<FallbackProvider<Succeeds, Fails>>(), 3);
+		assert!(!ongoing::<FallbackProvider<Succeeds, AlsoSucceeds>>());
+		assert!(ongoing::<FallbackProvider<Succeeds, Fails>>());
+		assert!(ongoing::<FallbackProvider<Fails, Succeeds>>());
+	}
+}
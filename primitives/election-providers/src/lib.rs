@@ -158,12 +158,35 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod fallback;
 pub mod onchain;
+pub mod seats;
 use sp_std::{prelude::*, fmt::Debug};
 
 /// Re-export some type as they are used in the interface.
 pub use sp_arithmetic::PerThing;
-pub use sp_npos_elections::{Assignment, ExtendedBalance, PerThing128, Supports, VoteWeight};
+pub use sp_npos_elections::{
+	Assignment, ExtendedBalance, PerThing128, Support, Supports, VoteWeight,
+};
+
+/// A measure of computation time, as returned by [`ElectionProvider::estimate_cost`].
+///
+/// This is the same type as `frame_support::weights::Weight`.
+pub type Weight = u64;
+
+/// Sort the targets and the voters of an election by their account id.
+///
+/// Election algorithms such as [`sp_npos_elections::seq_phragmen`] break ties between equally
+/// backed candidates based on the order of their inputs. Sorting the inputs into this canonical
+/// order guarantees that independent computations of an election over the same data, e.g. by
+/// different miners, yield the same winners.
+pub fn sort_election_inputs<AccountId: Ord>(
+	targets: &mut Vec<AccountId>,
+	voters: &mut Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) {
+	targets.sort();
+	voters.sort_by(|(x, _, _), (y, _, _)| x.cmp(y));
+}
 
 /// Something that can provide the data to an [`ElectionProvider`].
 pub trait ElectionDataProvider<AccountId, BlockNumber> {
@@ -175,6 +198,27 @@ pub trait ElectionDataProvider<AccountId, BlockNumber> {
 	/// Note that if a notion of self-vote exists, it should be represented here.
 	fn voters() -> Vec<(AccountId, VoteWeight, Vec<AccountId>)>;
 
+	/// At most `limit` voters of [`Self::voters`], in the same order, starting right after the
+	/// voter `last`, or from the first voter if `None`.
+	///
+	/// This allows the voters to be read over multiple blocks, in which case they might change in
+	/// between calls. By default, all the voters are read and the requested chunk is returned;
+	/// implementors should override it if the voters can be read incrementally. Note that the
+	/// default starts over from the first voter if `last` is no longer a voter.
+	fn voters_after(
+		last: Option<&AccountId>,
+		limit: u32,
+	) -> Vec<(AccountId, VoteWeight, Vec<AccountId>)>
+	where
+		AccountId: PartialEq,
+	{
+		let voters = Self::voters();
+		let skip = last
+			.and_then(|last| voters.iter().position(|(who, _, _)| who == last))
+			.map_or(0, |index| index + 1);
+		voters.into_iter().skip(skip).take(limit as usize).collect()
+	}
+
 	/// The number of targets to elect.
 	fn desired_targets() -> u32;
 
@@ -186,6 +230,37 @@ pub trait ElectionDataProvider<AccountId, BlockNumber> {
 	/// This is only useful for stateful election providers.
 	fn next_election_prediction(now: BlockNumber) -> BlockNumber;
 
+	/// Check whether `who` is a viable candidate, i.e. can currently be elected.
+	///
+	/// The viable candidates are a subset of [`Self::targets`]. By default, all targets are viable.
+	fn is_viable_candidate(_who: &AccountId) -> bool {
+		true
+	}
+
+	/// Adjust the raw vote weight of voter `who`, as returned by [`Self::voters`], before it is
+	/// used in the election.
+	///
+	/// This can be used to, for example, apply a conviction multiplier. By default, this is the
+	/// identity function.
+	fn voter_weight_adjustment(_who: &AccountId, raw: VoteWeight) -> VoteWeight {
+		raw
+	}
+
+	/// An upper bound on the number of voters that [`Self::voters`] would return, if known.
+	///
+	/// This can be used to plan for the size of the data ahead of fetching it. By default, no
+	/// bound is known.
+	fn maybe_max_voters() -> Option<u32> {
+		None
+	}
+
+	/// An upper bound on the number of targets that [`Self::targets`] would return, if known.
+	///
+	/// By default, no bound is known.
+	fn maybe_max_targets() -> Option<u32> {
+		None
+	}
+
 	/// Utility function only to be used in benchmarking scenarios, to be implemented optionally,
 	/// else a noop.
 	#[cfg(any(feature = "runtime-benchmarks", test))]
@@ -228,6 +303,39 @@ pub trait ElectionProvider<AccountId, BlockNumber> {
 	///
 	/// The result is returned in a target major format, namely as vector of  supports.
 	fn elect() -> Result<Supports<AccountId>, Self::Error>;
+
+	/// Elect a new set of winners, feeding each winner and its support to `f`, one at a time.
+	///
+	/// The default implementation is built on top of [`Self::elect`], and thus still allocates
+	/// all the supports at once. Memory-sensitive implementations can override it.
+	fn elect_each<F: FnMut(AccountId, Support<AccountId>)>(mut f: F) -> Result<(), Self::Error> {
+		Self::elect()
+			.map(|supports| supports.into_iter().for_each(|(who, support)| f(who, support)))
+	}
+
+	/// Estimate the weight of [`Self::elect`], given the number of voters and targets.
+	///
+	/// This can be used to compare the expense of different election providers before committing
+	/// to one. The default implementation returns [`Weight::max_value`], meaning unknown.
+	fn estimate_cost(_voters: u32, _targets: u32) -> Weight {
+		Weight::max_value()
+	}
+
+	/// Elect a new set of winners, and allocate `seats` among them proportional to their backing.
+	///
+	/// See [`seats::largest_remainder_seats`].
+	fn elect_seats(seats: u32) -> Result<Vec<(AccountId, u32)>, Self::Error> {
+		Self::elect().map(|supports| seats::largest_remainder_seats(supports, seats))
+	}
+
+	/// Whether an election is currently in progress.
+	///
+	/// Stateful providers that compute the election ahead of [`Self::elect`] should return true
+	/// while doing so, such that no other election is triggered in the meantime. Stateless
+	/// providers, the default, are never ongoing.
+	fn ongoing() -> bool {
+		false
+	}
 }
 
 #[cfg(feature = "std")]
@@ -15,9 +15,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! An implementation of [`ElectionProvider`] that does an on-chain sequential phragmen.
+//! Implementations of [`ElectionProvider`] that compute the election on-chain:
+//!
+//! - [`OnChainSequentialPhragmen`], using [`sp_npos_elections::seq_phragmen`].
+//! - [`OnChainPhragmms`], using [`sp_npos_elections::phragmms`], which is more expensive but
+//!   yields a better score.
+//!
+//! Both are configured with the same [`Config`]. The inputs of the election are sorted with
+//! [`crate::sort_election_inputs`], thus the outcome does not depend on the order in which the data
+//! provider returns them, unless [`Config::INPUTS_PRESORTED`] is set.
 
-use crate::{ElectionDataProvider, ElectionProvider};
+use crate::{ElectionDataProvider, ElectionProvider, Weight};
 use sp_npos_elections::*;
 use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 
@@ -26,6 +34,12 @@ use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 pub enum Error {
 	/// An internal error in the NPoS elections crate.
 	NposElections(sp_npos_elections::Error),
+	/// An internal error of the phragmms algorithm.
+	Phragmms(&'static str),
+	/// The data provider returned more voters than [`Config::MAX_VOTERS`].
+	TooManyVoters,
+	/// The data provider returned more targets than [`Config::MAX_TARGETS`].
+	TooManyTargets,
 }
 
 impl From<sp_npos_elections::Error> for Error {
@@ -43,9 +57,17 @@ impl From<sp_npos_elections::Error> for Error {
 /// This can be very expensive to run frequently on-chain. Use with care.
 pub struct OnChainSequentialPhragmen<T: Config>(PhantomData<T>);
 
-/// Configuration trait of [`OnChainSequentialPhragmen`].
+/// Same as [`OnChainSequentialPhragmen`], but uses [`sp_npos_elections::phragmms`] instead of
+/// [`sp_npos_elections::seq_phragmen`].
 ///
-/// Note that this is similar to a pallet traits, but [`OnChainSequentialPhragmen`] is not a pallet.
+/// ### Warning
+///
+/// Phragmms is more expensive than sequential phragmen. Use with even more care.
+pub struct OnChainPhragmms<T: Config>(PhantomData<T>);
+
+/// Configuration trait of [`OnChainSequentialPhragmen`] and [`OnChainPhragmms`].
+///
+/// Note that this is similar to a pallet traits, but neither of them is a pallet.
 pub trait Config {
 	/// The account identifier type.
 	type AccountId: IdentifierT;
@@ -55,6 +77,165 @@ pub trait Config {
 	type Accuracy: PerThing128;
 	/// Something that provides the data for election.
 	type DataProvider: ElectionDataProvider<Self::AccountId, Self::BlockNumber>;
+
+	/// The number of balancing iterations to run after the election.
+	///
+	/// Zero, the default, means no balancing. Each iteration makes the election more expensive,
+	/// but yields a more evenly distributed support among the winners.
+	const BALANCING_ITERATIONS: usize = 0;
+
+	/// Whether to run [`sp_npos_elections::reduce`] on the staked assignments before building the
+	/// supports.
+	///
+	/// Reducing does not alter the total backing of any winner, but it removes redundant edges,
+	/// hence shrinking the resulting exposures. Disabled by default.
+	const REDUCE: bool = false;
+
+	/// The maximum number of voters that the election is willing to process.
+	///
+	/// If the data provider hints at, or returns, more voters than this, the election fails with
+	/// [`Error::TooManyVoters`] rather than running an arbitrarily expensive computation. Only a
+	/// hint, see [`ElectionDataProvider::maybe_max_voters`], prevents the voters from being read.
+	const MAX_VOTERS: usize = usize::max_value();
+
+	/// The maximum number of targets that the election is willing to process.
+	///
+	/// If the data provider hints at, or returns, more targets than this, the election fails with
+	/// [`Error::TooManyTargets`].
+	const MAX_TARGETS: usize = usize::max_value();
+
+	/// The weight of a single unit of work of the election, used by
+	/// [`ElectionProvider::estimate_cost`].
+	///
+	/// The election is modelled as `voters * targets * (1 + BALANCING_ITERATIONS)` units of work.
+	/// The default, [`Weight::max_value`], means that the cost is unknown.
+	const WEIGHT_PER_UNIT: Weight = Weight::max_value();
+
+	/// Whether the data provider already returns its inputs sorted by account id, with no voter
+	/// appearing more than once.
+	///
+	/// If set, the inputs are not sorted again, and the stake of each voter is looked up with a
+	/// binary search over the voters instead of being collected into a map. Setting this while the
+	/// inputs are not sorted leads to voters having no stake, i.e. a wrong election outcome.
+	const INPUTS_PRESORTED: bool = false;
+}
+
+/// The inputs of an on-chain election: the desired number of targets, the targets, and the voters.
+type ElectionInputs<AccountId> =
+	(usize, Vec<AccountId>, Vec<(AccountId, VoteWeight, Vec<AccountId>)>);
+
+/// Fetch the inputs of the election from [`Config::DataProvider`], ensuring that they are within
+/// the bounds of `T`.
+///
+/// The bounds hinted by the data provider, if any, are checked before anything is fetched.
+fn election_inputs<T: Config>() -> Result<ElectionInputs<T::AccountId>, Error> {
+	let exceeds = |hint: Option<u32>, max: usize| hint.map_or(false, |h| h as usize > max);
+	if exceeds(T::DataProvider::maybe_max_voters(), T::MAX_VOTERS) {
+		return Err(Error::TooManyVoters);
+	}
+	if exceeds(T::DataProvider::maybe_max_targets(), T::MAX_TARGETS) {
+		return Err(Error::TooManyTargets);
+	}
+
+	let voters = T::DataProvider::voters();
+	if voters.len() > T::MAX_VOTERS {
+		return Err(Error::TooManyVoters);
+	}
+	let mut voters = voters
+		.into_iter()
+		.map(|(who, stake, votes)| {
+			let stake = T::DataProvider::voter_weight_adjustment(&who, stake);
+			(who, stake, votes)
+		})
+		.collect::<Vec<_>>();
+	let mut targets = T::DataProvider::targets();
+	if targets.len() > T::MAX_TARGETS {
+		return Err(Error::TooManyTargets);
+	}
+	if !T::INPUTS_PRESORTED {
+		crate::sort_election_inputs(&mut targets, &mut voters);
+	}
+	let desired_targets = T::DataProvider::desired_targets() as usize;
+
+	Ok((desired_targets, targets, voters))
+}
+
+/// The balancing configuration of `T`, as expected by the election algorithms.
+fn balancing<T: Config>() -> Option<(usize, ExtendedBalance)> {
+	match T::BALANCING_ITERATIONS {
+		0 => None,
+		iterations => Some((iterations, 0)),
+	}
+}
+
+/// Convert the result of an election algorithm into supports.
+fn to_supports_of<T: Config>(
+	result: ElectionResult<T::AccountId, T::Accuracy>,
+	stakes: Stakes<'_, T::AccountId>,
+) -> Result<Supports<T::AccountId>, Error> {
+	let ElectionResult { winners, assignments } = result;
+
+	let stake_of = |w: &T::AccountId| -> VoteWeight { stakes.stake_of(w) };
+
+	let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)?;
+	if T::REDUCE {
+		reduce(&mut staked);
+	}
+	let winners = to_without_backing(winners);
+
+	to_supports(&winners, &staked).map_err(Error::from)
+}
+
+/// The estimated cost of an election of `T` with the given number of voters and targets.
+fn estimate_cost_of<T: Config>(voters: u32, targets: u32) -> Weight {
+	(voters as Weight)
+		.saturating_mul(targets as Weight)
+		.saturating_mul((T::BALANCING_ITERATIONS as Weight).saturating_add(1))
+		.saturating_mul(T::WEIGHT_PER_UNIT)
+}
+
+/// The stake of each voter.
+enum Stakes<'a, AccountId> {
+	/// Looked up in a map, for inputs of any order.
+	Map(BTreeMap<AccountId, VoteWeight>),
+	/// Looked up with a binary search over the voters, for inputs sorted by account id.
+	Sorted(&'a [(AccountId, VoteWeight, Vec<AccountId>)]),
+}
+
+impl<'a, AccountId: IdentifierT> Stakes<'a, AccountId> {
+	/// The stakes of `voters`, based on [`Config::INPUTS_PRESORTED`], along with the voters to be
+	/// passed on to the election algorithm.
+	///
+	/// If the inputs are presorted, `voters` is kept for the lookups and only their votes are moved
+	/// out, otherwise `voters` is left empty.
+	fn of<T: Config<AccountId = AccountId>>(
+		voters: &'a mut Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	) -> (Self, Vec<(AccountId, VoteWeight, Vec<AccountId>)>) {
+		if T::INPUTS_PRESORTED {
+			let inputs = voters
+				.iter_mut()
+				.map(|(v, s, votes)| (v.clone(), *s, sp_std::mem::take(votes)))
+				.collect();
+			(Stakes::Sorted(voters), inputs)
+		} else {
+			let mut stake_map: BTreeMap<AccountId, VoteWeight> = BTreeMap::new();
+			voters.iter().for_each(|(v, s, _)| {
+				stake_map.insert(v.clone(), *s);
+			});
+			(Stakes::Map(stake_map), sp_std::mem::take(voters))
+		}
+	}
+
+	/// The stake of `who`, zero if unknown.
+	fn stake_of(&self, who: &AccountId) -> VoteWeight {
+		match self {
+			Stakes::Map(stake_map) => stake_map.get(who).cloned().unwrap_or_default(),
+			Stakes::Sorted(voters) => voters
+				.binary_search_by(|(v, _, _)| v.cmp(who))
+				.map(|index| voters[index].1)
+				.unwrap_or_default(),
+		}
+	}
 }
 
 impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for OnChainSequentialPhragmen<T> {
@@ -62,28 +243,38 @@ impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for OnChainSequen
 	type DataProvider = T::DataProvider;
 
 	fn elect() -> Result<Supports<T::AccountId>, Self::Error> {
-		let voters = Self::DataProvider::voters();
-		let targets = Self::DataProvider::targets();
-		let desired_targets = Self::DataProvider::desired_targets() as usize;
+		let (desired_targets, targets, mut voters) = election_inputs::<T>()?;
+		let (stakes, voters) = Stakes::of::<T>(&mut voters);
 
-		let mut stake_map: BTreeMap<T::AccountId, VoteWeight> = BTreeMap::new();
+		let result =
+			seq_phragmen::<_, T::Accuracy>(desired_targets, targets, voters, balancing::<T>())
+				.map_err(Error::from)?;
 
-		voters.iter().for_each(|(v, s, _)| {
-			stake_map.insert(v.clone(), *s);
-		});
+		to_supports_of::<T>(result, stakes)
+	}
 
-		let stake_of = |w: &T::AccountId| -> VoteWeight {
-			stake_map.get(w).cloned().unwrap_or_default()
-		};
+	fn estimate_cost(voters: u32, targets: u32) -> Weight {
+		estimate_cost_of::<T>(voters, targets)
+	}
+}
 
-		let ElectionResult { winners, assignments } =
-			seq_phragmen::<_, T::Accuracy>(desired_targets, targets, voters, None)
-				.map_err(Error::from)?;
+impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for OnChainPhragmms<T> {
+	type Error = Error;
+	type DataProvider = T::DataProvider;
+
+	fn elect() -> Result<Supports<T::AccountId>, Self::Error> {
+		let (desired_targets, targets, mut voters) = election_inputs::<T>()?;
+		let (stakes, voters) = Stakes::of::<T>(&mut voters);
 
-		let staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)?;
-		let winners = to_without_backing(winners);
+		let result =
+			phragmms::<_, T::Accuracy>(desired_targets, targets, voters, balancing::<T>())
+				.map_err(Error::Phragmms)?;
 
-		to_supports(&winners, &staked).map_err(Error::from)
+		to_supports_of::<T>(result, stakes)
+	}
+
+	fn estimate_cost(voters: u32, targets: u32) -> Weight {
+		estimate_cost_of::<T>(voters, targets)
 	}
 }
 
@@ -106,6 +297,96 @@ mod tests {
 
 	type OnChainPhragmen = OnChainSequentialPhragmen<Runtime>;
 
+	struct BalancedRuntime;
+	impl Config for BalancedRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::DataProvider;
+		const BALANCING_ITERATIONS: usize = 10;
+		const WEIGHT_PER_UNIT: Weight = 2;
+	}
+
+	type BalancedOnChainPhragmen = OnChainSequentialPhragmen<BalancedRuntime>;
+
+	struct ReducedRuntime;
+	impl Config for ReducedRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::DataProvider;
+		const BALANCING_ITERATIONS: usize = 10;
+		const REDUCE: bool = true;
+	}
+
+	type ReducedOnChainPhragmen = OnChainSequentialPhragmen<ReducedRuntime>;
+
+	struct BoundedVotersRuntime;
+	impl Config for BoundedVotersRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::DataProvider;
+		const MAX_VOTERS: usize = 2;
+	}
+
+	struct BoundedTargetsRuntime;
+	impl Config for BoundedTargetsRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::DataProvider;
+		const MAX_TARGETS: usize = 2;
+	}
+
+	struct HintedBoundsRuntime;
+	impl Config for HintedBoundsRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::HugeDataProvider;
+		const MAX_VOTERS: usize = 1_000;
+		const MAX_TARGETS: usize = 1_000;
+	}
+
+	struct PresortedRuntime;
+	impl Config for PresortedRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::DataProvider;
+		const INPUTS_PRESORTED: bool = true;
+	}
+
+	struct TiedRuntime;
+	impl Config for TiedRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::TiedDataProvider;
+	}
+
+	struct ReversedTiedRuntime;
+	impl Config for ReversedTiedRuntime {
+		type AccountId = AccountId;
+		type BlockNumber = BlockNumber;
+		type Accuracy = Perbill;
+		type DataProvider = mock_data_provider::ReversedTiedDataProvider;
+	}
+
+	/// Variance of the total backing of each winner, scaled by the number of winners.
+	fn support_variance(supports: &Supports<AccountId>) -> u128 {
+		let n = supports.len() as u128;
+		let sum = supports.iter().map(|(_, s)| s.total).sum::<u128>();
+		supports
+			.iter()
+			.map(|(_, s)| {
+				let diff = (s.total * n) as i128 - sum as i128;
+				(diff * diff) as u128
+			})
+			.sum::<u128>()
+	}
+
 	mod mock_data_provider {
 		use super::*;
 
@@ -132,6 +413,81 @@ mod tests {
 				0
 			}
 		}
+
+		/// Hints at more voters and targets than can be read.
+		pub struct HugeDataProvider;
+
+		impl ElectionDataProvider<AccountId, BlockNumber> for HugeDataProvider {
+			fn voters() -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+				unreachable!("voters must not be read beyond the bounds")
+			}
+
+			fn targets() -> Vec<AccountId> {
+				unreachable!("targets must not be read beyond the bounds")
+			}
+
+			fn desired_targets() -> u32 {
+				2
+			}
+
+			fn next_election_prediction(_: BlockNumber) -> BlockNumber {
+				0
+			}
+
+			fn maybe_max_voters() -> Option<u32> {
+				Some(1_001)
+			}
+
+			fn maybe_max_targets() -> Option<u32> {
+				Some(1_001)
+			}
+		}
+
+		/// Two equally backed targets, competing for a single seat.
+		pub struct TiedDataProvider;
+
+		impl ElectionDataProvider<AccountId, BlockNumber> for TiedDataProvider {
+			fn voters() -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+				vec![(1, 10, vec![10]), (2, 10, vec![20]), (3, 10, vec![10, 20])]
+			}
+
+			fn targets() -> Vec<AccountId> {
+				vec![10, 20]
+			}
+
+			fn desired_targets() -> u32 {
+				1
+			}
+
+			fn next_election_prediction(_: BlockNumber) -> BlockNumber {
+				0
+			}
+		}
+
+		/// Same as [`TiedDataProvider`], but with all inputs in reverse order.
+		pub struct ReversedTiedDataProvider;
+
+		impl ElectionDataProvider<AccountId, BlockNumber> for ReversedTiedDataProvider {
+			fn voters() -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+				let mut voters = TiedDataProvider::voters();
+				voters.reverse();
+				voters
+			}
+
+			fn targets() -> Vec<AccountId> {
+				let mut targets = TiedDataProvider::targets();
+				targets.reverse();
+				targets
+			}
+
+			fn desired_targets() -> u32 {
+				TiedDataProvider::desired_targets()
+			}
+
+			fn next_election_prediction(_: BlockNumber) -> BlockNumber {
+				0
+			}
+		}
 	}
 
 	#[test]
@@ -156,4 +512,143 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn onchain_phragmms_works() {
+		let phragmen = OnChainPhragmen::elect().unwrap();
+		let phragmms = OnChainPhragmms::<Runtime>::elect().unwrap();
+
+		let total_stake = |supports: &Supports<AccountId>| {
+			supports.iter().map(|(_, s)| s.total).sum::<ExtendedBalance>()
+		};
+		let is_consistent = |support: &Support<AccountId>| {
+			support.total == support.voters.iter().map(|(_, w)| *w).sum::<ExtendedBalance>()
+		};
+
+		for supports in vec![&phragmen, &phragmms] {
+			// correct number of winners..
+			assert_eq!(supports.len(), 2);
+			// ..with consistent supports..
+			assert!(supports.iter().all(|(_, s)| is_consistent(s)));
+			// ..backed by all the stake.
+			assert_eq!(total_stake(supports), 60);
+		}
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_elect_each_works() {
+		let mut streamed = vec![];
+		OnChainPhragmen::elect_each(|who, support| streamed.push((who, support))).unwrap();
+		assert_eq!(streamed, OnChainPhragmen::elect().unwrap());
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_is_independent_of_input_order() {
+		let winners_of = |supports: Supports<AccountId>| {
+			supports.into_iter().map(|(who, _)| who).collect::<Vec<_>>()
+		};
+
+		let tied = OnChainSequentialPhragmen::<TiedRuntime>::elect().unwrap();
+		let reversed = OnChainSequentialPhragmen::<ReversedTiedRuntime>::elect().unwrap();
+		assert_eq!(winners_of(tied.clone()).len(), 1);
+		assert_eq!(winners_of(tied), winners_of(reversed));
+
+		let tied = OnChainPhragmms::<TiedRuntime>::elect().unwrap();
+		let reversed = OnChainPhragmms::<ReversedTiedRuntime>::elect().unwrap();
+		assert_eq!(winners_of(tied), winners_of(reversed));
+	}
+
+	#[test]
+	fn onchain_presorted_inputs_work() {
+		assert_eq!(
+			OnChainSequentialPhragmen::<PresortedRuntime>::elect().unwrap(),
+			OnChainPhragmen::elect().unwrap(),
+		);
+		assert_eq!(
+			OnChainPhragmms::<PresortedRuntime>::elect().unwrap(),
+			OnChainPhragmms::<Runtime>::elect().unwrap(),
+		);
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_elect_seats_works() {
+		let winners = OnChainPhragmen::elect()
+			.unwrap()
+			.into_iter()
+			.map(|(who, _)| who)
+			.collect::<Vec<_>>();
+		let seats = OnChainPhragmen::elect_seats(10).unwrap();
+
+		assert_eq!(seats.iter().map(|(who, _)| *who).collect::<Vec<_>>(), winners);
+		assert_eq!(seats.iter().map(|(_, seats)| seats).sum::<u32>(), 10);
+	}
+
+	#[test]
+	fn onchain_estimate_cost_works() {
+		// unknown by default.
+		assert_eq!(OnChainPhragmen::estimate_cost(3, 4), Weight::max_value());
+
+		// 3 voters * 4 targets * (1 + 10) iterations * 2.
+		assert_eq!(BalancedOnChainPhragmen::estimate_cost(3, 4), 264);
+		assert_eq!(OnChainPhragmms::<BalancedRuntime>::estimate_cost(3, 4), 264);
+		assert_eq!(BalancedOnChainPhragmen::estimate_cost(0, 4), 0);
+		assert_eq!(
+			BalancedOnChainPhragmen::estimate_cost(u32::max_value(), u32::max_value()),
+			Weight::max_value(),
+		);
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_balancing_reduces_variance() {
+		let unbalanced = OnChainPhragmen::elect().unwrap();
+		let balanced = BalancedOnChainPhragmen::elect().unwrap();
+
+		// same winners, same total stake.
+		assert_eq!(
+			unbalanced.iter().map(|(w, _)| *w).collect::<Vec<_>>(),
+			balanced.iter().map(|(w, _)| *w).collect::<Vec<_>>(),
+		);
+		assert_eq!(
+			unbalanced.iter().map(|(_, s)| s.total).sum::<u128>(),
+			balanced.iter().map(|(_, s)| s.total).sum::<u128>(),
+		);
+
+		assert!(support_variance(&balanced) < support_variance(&unbalanced));
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_reduce_keeps_backing() {
+		let balanced = BalancedOnChainPhragmen::elect().unwrap();
+		let reduced = ReducedOnChainPhragmen::elect().unwrap();
+
+		// reducing never changes the backing of the winners..
+		assert_eq!(
+			balanced.iter().map(|(w, s)| (*w, s.total)).collect::<Vec<_>>(),
+			reduced.iter().map(|(w, s)| (*w, s.total)).collect::<Vec<_>>(),
+		);
+
+		// ..but it might remove some edges.
+		let edges = |supports: &Supports<AccountId>| {
+			supports.iter().map(|(_, s)| s.voters.len()).sum::<usize>()
+		};
+		assert!(edges(&reduced) <= edges(&balanced));
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_respects_bounds() {
+		assert_eq!(
+			OnChainSequentialPhragmen::<BoundedVotersRuntime>::elect().unwrap_err(),
+			Error::TooManyVoters,
+		);
+		assert_eq!(
+			OnChainSequentialPhragmen::<BoundedTargetsRuntime>::elect().unwrap_err(),
+			Error::TooManyTargets,
+		);
+
+		// the hints of the data provider are checked before anything is read.
+		assert_eq!(
+			OnChainSequentialPhragmen::<HintedBoundsRuntime>::elect().unwrap_err(),
+			Error::TooManyVoters,
+		);
+	}
 }
@@ -0,0 +1,127 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Allocation of a fixed number of seats among the winners of an election, proportional to their
+//! backing.
+
+use crate::{ExtendedBalance, Supports};
+use sp_std::prelude::*;
+
+/// Allocate `seats` among the winners of `supports`, proportional to their total backing, using
+/// the largest remainder method.
+///
+/// Each winner first receives the integer part of its quota, `total * seats / sum_of_totals`. The
+/// remaining seats are then given, one each, to the winners with the largest remainders. Ties are
+/// broken in favour of the larger backing, and then the earlier position in `supports`.
+///
+/// The order of `supports` is retained in the output. If the sum of totals is zero, no seats are
+/// allocated. The totals are expected to be far below `ExtendedBalance::max_value() / seats`.
+pub fn largest_remainder_seats<AccountId>(
+	supports: Supports<AccountId>,
+	seats: u32,
+) -> Vec<(AccountId, u32)> {
+	let sum: ExtendedBalance =
+		supports.iter().fold(0, |acc, (_, support)| acc.saturating_add(support.total));
+	if sum == 0 {
+		return supports.into_iter().map(|(who, _)| (who, 0)).collect();
+	}
+
+	// (index, total, remainder) of each winner, and the seats allocated so far.
+	let mut remainders = Vec::with_capacity(supports.len());
+	let mut allocated = 0u32;
+	let mut allocation = supports
+		.into_iter()
+		.enumerate()
+		.map(|(index, (who, support))| {
+			let scaled = support.total.saturating_mul(seats as ExtendedBalance);
+			let quota = (scaled / sum) as u32;
+			allocated = allocated.saturating_add(quota);
+			remainders.push((index, support.total, scaled % sum));
+			(who, quota)
+		})
+		.collect::<Vec<_>>();
+
+	remainders.sort_by(|(x_index, x_total, x_rem), (y_index, y_total, y_rem)| {
+		y_rem.cmp(x_rem).then(y_total.cmp(x_total)).then(x_index.cmp(y_index))
+	});
+	remainders
+		.into_iter()
+		.take(seats.saturating_sub(allocated) as usize)
+		.for_each(|(index, _, _)| allocation[index].1 += 1);
+
+	allocation
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Support;
+
+	fn supports_of(totals: &[(u64, ExtendedBalance)]) -> Supports<u64> {
+		totals
+			.iter()
+			.map(|(who, total)| (*who, Support { total: *total, voters: vec![] }))
+			.collect()
+	}
+
+	#[test]
+	fn largest_remainder_seats_works() {
+		// quotas of 4.2, 3.5 and 2.3: one seat is left to the largest remainder.
+		assert_eq!(
+			largest_remainder_seats(supports_of(&[(1, 42), (2, 35), (3, 23)]), 10),
+			vec![(1, 4), (2, 4), (3, 2)],
+		);
+
+		// exact quotas.
+		assert_eq!(
+			largest_remainder_seats(supports_of(&[(1, 50), (2, 30), (3, 20)]), 10),
+			vec![(1, 5), (2, 3), (3, 2)],
+		);
+
+		// all seats are always allocated.
+		let seats = largest_remainder_seats(supports_of(&[(1, 1), (2, 1), (3, 1)]), 7);
+		assert_eq!(seats.iter().map(|(_, s)| s).sum::<u32>(), 7);
+	}
+
+	#[test]
+	fn largest_remainder_seats_breaks_ties() {
+		// quotas of 0.5 and 1.5: equal remainders, the larger backing wins.
+		assert_eq!(
+			largest_remainder_seats(supports_of(&[(1, 10), (2, 30)]), 2),
+			vec![(1, 0), (2, 2)],
+		);
+
+		// equal backing, the earlier position wins.
+		assert_eq!(
+			largest_remainder_seats(supports_of(&[(1, 10), (2, 10), (3, 10)]), 2),
+			vec![(1, 1), (2, 1), (3, 0)],
+		);
+	}
+
+	#[test]
+	fn largest_remainder_seats_empty() {
+		assert!(largest_remainder_seats(supports_of(&[]), 10).is_empty());
+		assert_eq!(
+			largest_remainder_seats(supports_of(&[(1, 0), (2, 0)]), 10),
+			vec![(1, 0), (2, 0)],
+		);
+		assert_eq!(
+			largest_remainder_seats(supports_of(&[(1, 10), (2, 20)]), 0),
+			vec![(1, 0), (2, 0)],
+		);
+	}
+}
@@ -148,6 +148,8 @@ fn struct_def(
 	let edge_count_impl = edge_count_impl(count);
 	let unique_targets_impl = unique_targets_impl(count);
 	let remove_voter_impl = remove_voter_impl(count);
+	let merge_impl = merge_impl(count);
+	let sanity_check_impl = sanity_check_impl(count);
 
 	let derives_and_maybe_compact_encoding = if compact_encoding {
 		// custom compact encoding.
@@ -215,6 +217,17 @@ fn struct_def(
 				return false
 			}
 
+			fn merge(mut self, other: Self) -> Result<Self, _npos::Error> {
+				let mut voters: Vec<Self::Voter> = Vec::with_capacity(self.voter_count());
+				#merge_impl
+				Ok(self)
+			}
+
+			fn sanity_check(&self) -> Result<(), _npos::Error> {
+				#sanity_check_impl
+				Ok(())
+			}
+
 			fn from_assignment<FV, FT, A>(
 				assignments: Vec<_npos::Assignment<A, #weight_type>>,
 				index_of_voter: FV,
@@ -288,6 +301,73 @@ fn remove_voter_impl(count: usize) -> TokenStream2 {
 	}
 }
 
+fn merge_impl(count: usize) -> TokenStream2 {
+	let voter_of = |c: usize| if c == 1 { quote!(|(v, _)| *v) } else { quote!(|(v, _, _)| *v) };
+
+	let collect_self = (1..=count).map(|c| {
+		let field_name = field_name_for(c);
+		let voter_of = voter_of(c);
+		quote!(
+			voters.extend(self.#field_name.iter().map(#voter_of));
+		)
+	}).collect::<TokenStream2>();
+
+	let check_other = (1..=count).map(|c| {
+		let field_name = field_name_for(c);
+		let voter_of = voter_of(c);
+		quote!(
+			if other.#field_name.iter().map(#voter_of).any(|v| voters.binary_search(&v).is_ok()) {
+				return Err(_npos::Error::CompactDuplicateVoter);
+			}
+		)
+	}).collect::<TokenStream2>();
+
+	let extend = (1..=count).map(|c| {
+		let field_name = field_name_for(c);
+		quote!(
+			self.#field_name.extend(other.#field_name);
+		)
+	}).collect::<TokenStream2>();
+
+	quote! {
+		#collect_self
+		voters.sort();
+		#check_other
+		#extend
+	}
+}
+
+fn sanity_check_impl(count: usize) -> TokenStream2 {
+	// a single vote can not be duplicate.
+	let sanity_check_impl_double = {
+		let field_name = field_name_for(2);
+		quote! {
+			if self.#field_name.iter().any(|(_, (t1, _), t2)| t1 == t2) {
+				return Err(_npos::Error::CompactDuplicateTarget);
+			}
+		}
+	};
+
+	let sanity_check_impl_rest = (3..=count).map(|c| {
+		let field_name = field_name_for(c);
+		quote! {
+			if self.#field_name.iter().any(|(_, inners, t_last)| {
+				let mut targets = inners.iter().map(|(t, _)| *t).collect::<Vec<Self::Target>>();
+				targets.push(*t_last);
+				targets.sort();
+				targets.windows(2).any(|w| w[0] == w[1])
+			}) {
+				return Err(_npos::Error::CompactDuplicateTarget);
+			}
+		}
+	}).collect::<TokenStream2>();
+
+	quote! {
+		#sanity_check_impl_double
+		#sanity_check_impl_rest
+	}
+}
+
 fn len_impl(count: usize) -> TokenStream2 {
 	(1..=count).map(|c| {
 		let field_name = field_name_for(c);
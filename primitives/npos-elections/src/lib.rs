@@ -198,6 +198,18 @@ pub trait CompactSolution: Sized {
 	/// `self.len()`.
 	fn remove_voter(&mut self, to_remove: Self::Voter) -> bool;
 
+	/// Merge `other` into `self`, as if both were computed together.
+	///
+	/// This is meant to combine solutions over disjoint sets of voters. Returns
+	/// [`Error::CompactDuplicateVoter`] if any voter is present in both.
+	fn merge(self, other: Self) -> Result<Self, Error>;
+
+	/// Ensure that no voter lists the same target more than once.
+	///
+	/// Such a solution decodes fine, but yields an assignment with duplicate edges. Returns
+	/// [`Error::CompactDuplicateTarget`] if any voter does so.
+	fn sanity_check(&self) -> Result<(), Error>;
+
 	/// Compute the score of this compact solution type.
 	fn score<A, FS>(
 		self,
@@ -243,6 +255,10 @@ pub enum Error {
 	ArithmeticError(&'static str),
 	/// The data provided to create support map was invalid.
 	InvalidSupportEdge,
+	/// The same voter is present in two compact solutions that were being merged.
+	CompactDuplicateVoter,
+	/// A voter of a compact solution lists the same target more than once.
+	CompactDuplicateTarget,
 }
 
 /// A type which is used in the API of this crate as a numeric weight of a vote, most often the
@@ -1281,6 +1281,98 @@ mod solution_type {
 		);
 	}
 
+	#[test]
+	fn merge_works() {
+		let first = TestSolutionCompact {
+			votes1: vec![(0, 2)],
+			votes2: vec![(2, (0, TestAccuracy::from_percent(80)), 1)],
+			..Default::default()
+		};
+		let second = TestSolutionCompact {
+			votes1: vec![(1, 6)],
+			votes3: vec![
+				(
+					4,
+					[(3, TestAccuracy::from_percent(50)), (4, TestAccuracy::from_percent(25))],
+					5,
+				),
+			],
+			..Default::default()
+		};
+
+		// disjoint voters.
+		let merged = first.clone().merge(second.clone()).unwrap();
+		assert_eq!(
+			merged,
+			TestSolutionCompact {
+				votes1: vec![(0, 2), (1, 6)],
+				votes2: vec![(2, (0, TestAccuracy::from_percent(80)), 1)],
+				votes3: vec![
+					(
+						4,
+						[(3, TestAccuracy::from_percent(50)), (4, TestAccuracy::from_percent(25))],
+						5,
+					),
+				],
+				..Default::default()
+			},
+		);
+		assert_eq!(merged.voter_count(), first.voter_count() + second.voter_count());
+
+		// overlapping voters, even if in a different field.
+		let overlapping = TestSolutionCompact { votes1: vec![(2, 3)], ..Default::default() };
+		assert_eq!(
+			first.clone().merge(overlapping).unwrap_err(),
+			PhragmenError::CompactDuplicateVoter,
+		);
+		assert_eq!(merged.merge(second).unwrap_err(), PhragmenError::CompactDuplicateVoter);
+
+		// merging with an empty solution is a no-op.
+		assert_eq!(first.clone().merge(Default::default()).unwrap(), first);
+	}
+
+	#[test]
+	fn sanity_check_works() {
+		let valid = TestSolutionCompact {
+			votes1: vec![(0, 2)],
+			votes2: vec![(1, (0, TestAccuracy::from_percent(80)), 1)],
+			votes3: vec![
+				(
+					2,
+					[(3, TestAccuracy::from_percent(50)), (4, TestAccuracy::from_percent(25))],
+					5,
+				),
+			],
+			..Default::default()
+		};
+		assert_eq!(valid.sanity_check(), Ok(()));
+
+		let duplicate_in_double = TestSolutionCompact {
+			votes2: vec![(1, (0, TestAccuracy::from_percent(80)), 0)],
+			..Default::default()
+		};
+		assert_eq!(
+			duplicate_in_double.sanity_check().unwrap_err(),
+			PhragmenError::CompactDuplicateTarget,
+		);
+
+		// the last target duplicates one of the inner ones.
+		let duplicate_in_rest = TestSolutionCompact {
+			votes3: vec![
+				(
+					2,
+					[(3, TestAccuracy::from_percent(50)), (4, TestAccuracy::from_percent(25))],
+					3,
+				),
+			],
+			..Default::default()
+		};
+		assert_eq!(
+			duplicate_in_rest.sanity_check().unwrap_err(),
+			PhragmenError::CompactDuplicateTarget,
+		);
+	}
+
 	#[test]
 	fn basic_from_and_into_compact_works_assignments() {
 		let voters = vec![